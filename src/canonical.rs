@@ -0,0 +1,205 @@
+//! Deterministic formatting of frozen values.
+//!
+//! The [`Debug`] output of hash-based collections follows their iteration
+//! order, which changes from run to run. [`Canonical`] formats a value like
+//! `Debug` does, but writes the entries of unordered collections sorted by
+//! key, so the output is byte-stable and suitable for snapshot tests and logs.
+//! Unordered collections therefore only implement [`FmtCanonical`] when their
+//! keys implement [`Ord`].
+//!
+//! Types opt in by implementing [`FmtCanonical`]. This is implemented for
+//! every frozen form in this crate; types whose `Debug` output is already
+//! deterministic can implement it with an empty `impl` block.
+//!
+//! The frozen forms of sets and maps also implement
+//! [`Display`](core::fmt::Display) with set and map literal syntax, such as
+//! `{a, b}` and `{a: 1, b: 2}`, again sorted by key.
+use core::fmt::{self, Debug, Formatter};
+
+use core::time::Duration;
+
+use crate::frozen_core::FrozenMutRef;
+use crate::{Freezable, Frozen};
+
+/// A value which is formatted deterministically by [`Debug`].
+///
+/// Created with [`Frozen::canonical`] or [`Canonical::new`].
+///
+/// Unordered collections are written as map or set literals sorted by key,
+/// without the name of the collection type. This recurses through frozen
+/// values, collections, options, arrays and tuples, and any other type implementing [`FmtCanonical`].
+pub struct Canonical<'a, T: ?Sized> {
+    value: &'a T,
+    options: Options,
+}
+impl<'a, T: ?Sized> Canonical<'a, T> {
+    /// Format `value` deterministically.
+    pub fn new(value: &'a T) -> Self {
+        Canonical {
+            value,
+            options: Options { wrappers: true },
+        }
+    }
+
+    /// Leave out the `Frozen(...)` around every frozen value, including
+    /// nested ones.
+    #[must_use]
+    pub fn without_wrappers(mut self) -> Self {
+        self.options.wrappers = false;
+        self
+    }
+}
+impl<T: ?Sized> Clone for Canonical<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for Canonical<'_, T> {
+}
+impl<T: FmtCanonical + ?Sized> Debug for Canonical<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt_canonical(self.options, f)
+    }
+}
+
+/// How a [`Canonical`] value is formatted, passed down to nested values.
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    wrappers: bool,
+}
+impl Options {
+    /// Format a nested value with the same options.
+    pub fn nested<T: ?Sized>(self, value: &T) -> Canonical<'_, T> {
+        Canonical {
+            value,
+            options: self,
+        }
+    }
+}
+
+impl<T: Freezable + ?Sized> Frozen<T> {
+    /// Format this value deterministically. See [`Canonical`].
+    pub fn canonical(&self) -> Canonical<'_, Self> {
+        Canonical::new(self)
+    }
+}
+
+/// A type which can be formatted deterministically by [`Canonical`].
+///
+/// The provided method writes the type's own [`Debug`] output, which suits
+/// types with deterministic output and no nested frozen values. Collections
+/// should format each item with [`Options::nested`], and write unordered
+/// items in a stable order.
+pub trait FmtCanonical: Debug {
+    /// Format this value deterministically.
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        let _ = options;
+        self.fmt(f)
+    }
+}
+macro_rules! impl_fmt_canonical {
+    ($($impl_type:ty),*) => {
+        $(
+            impl FmtCanonical for $impl_type {
+            }
+        )*
+    };
+}
+#[cfg(feature = "alloc")]
+pub(crate) use impl_fmt_canonical;
+impl_fmt_canonical!(
+    (),
+    bool,
+    char,
+    f32,
+    f64,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    str,
+    Duration
+);
+impl<T: FmtCanonical + ?Sized> FmtCanonical for &T {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        (**self).fmt_canonical(options, f)
+    }
+}
+impl<T: Freezable + FmtCanonical + ?Sized> FmtCanonical for FrozenMutRef<'_, T> {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenMutRef")
+            .field(&options.nested(&**self))
+            .finish()
+    }
+}
+impl<T: Freezable + ?Sized> FmtCanonical for Frozen<T>
+where
+    T::Frozen: FmtCanonical,
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        let inner = options.nested(&self.0);
+        if options.wrappers {
+            f.debug_tuple("Frozen").field(&inner).finish()
+        } else {
+            inner.fmt(f)
+        }
+    }
+}
+impl<T: FmtCanonical> FmtCanonical for [T] {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|item| options.nested(item)))
+            .finish()
+    }
+}
+impl<T: FmtCanonical, const N: usize> FmtCanonical for [T; N] {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        options.nested(self.as_slice()).fmt(f)
+    }
+}
+impl<T: FmtCanonical> FmtCanonical for Option<T> {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(value) => f.debug_tuple("Some").field(&options.nested(value)).finish(),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($param:ident),*) => {
+        impl<$($param: FmtCanonical),*> FmtCanonical for ($($param,)*) {
+            fn fmt_canonical(
+                &self,
+                options: Options,
+                f: &mut Formatter<'_>,
+            ) -> fmt::Result {
+                #[allow(non_snake_case)]
+                let ($($param,)*) = self;
+
+                f.debug_tuple("")
+                    $(.field(&options.nested($param)))*
+                    .finish()
+            }
+        }
+    };
+}
+tuple_impl!(A, B);
+tuple_impl!(A, B, C);
+tuple_impl!(A, B, C, D);
+tuple_impl!(A, B, C, D, E);
+tuple_impl!(A, B, C, D, E, F);
+tuple_impl!(A, B, C, D, E, F, G);
+tuple_impl!(A, B, C, D, E, F, G, H);
+tuple_impl!(A, B, C, D, E, F, G, H, I);
+tuple_impl!(A, B, C, D, E, F, G, H, I, J);
+tuple_impl!(A, B, C, D, E, F, G, H, I, J, K);
+tuple_impl!(A, B, C, D, E, F, G, H, I, J, K, L);
@@ -0,0 +1,126 @@
+//! Conversions between frozen collections and the collections they thaw into.
+//!
+//! [`Unfreezable`] covers the thaw targets each collection was written with.
+//! [`FromFrozen`] and [`ThawInto`] fill in the rest: any frozen collection
+//! thaws into any collection which can be built from its elements (see
+//! [`FromElements`]), thawing each element on the way. A frozen `HashSet`
+//! thaws into a `BTreeSet`, a frozen `HashMap` into a `BTreeMap` or a
+//! `Vec<(K, V)>`, and so on.
+//!
+//! Frozen collections whose forms are compatible also convert into each other
+//! with [`From`] and [`TryFrom`], without thawing anything. For example, a
+//! `Frozen<VecDeque<T>>` converts into a `Frozen<Vec<T>>` for free, and from
+//! there into a `Frozen<[T; N]>` if it has exactly `N` elements.
+//!
+//! ```
+//! use std::collections::{BTreeMap, HashMap};
+//!
+//! use freezable::convert::ThawInto;
+//! use freezable::prelude::*;
+//!
+//! let frozen = HashMap::from([(2, 'b'), (1, 'a')]).freeze();
+//! let sorted: BTreeMap<u8, char> = (&frozen).thaw_into();
+//! let pairs: Vec<(u8, char)> = frozen.thaw_into();
+//! assert_eq!(sorted.into_iter().collect::<Vec<_>>(), [(1, 'a'), (2, 'b')]);
+//! assert_eq!(pairs.len(), 2);
+//! ```
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
+
+/// A collection which can be built from a sequence of elements, and so be the
+/// target of a [`FromFrozen`] conversion.
+pub trait FromElements: FromIterator<Self::Element> {
+    /// The type of each element: `T` for sequences and sets, and `(K, V)` for
+    /// maps.
+    type Element;
+}
+/// Thawing into a frozen collection freezes the thawed elements again, which
+/// converts between frozen forms that have no direct conversion.
+impl<D: FromElements + Freezable> FromElements for Frozen<D> {
+    type Element = D::Element;
+}
+
+/// A thawed element, built from an element `E` of a frozen collection.
+///
+/// Frozen collections yield `Frozen<T>` or `&Frozen<T>` for each element, or
+/// a pair of them for each map entry; these thaw into anything implementing
+/// [`Unfreezable`] or [`CloneUnfreezable`] respectively.
+pub trait ThawElement<E>: Sized {
+    /// Thaw the element.
+    fn thaw_element(element: E) -> Self;
+}
+impl<T: Freezable + ?Sized, U: Unfreezable<T>> ThawElement<Frozen<T>> for U {
+    fn thaw_element(element: Frozen<T>) -> Self {
+        element.thaw()
+    }
+}
+impl<T: Freezable + ?Sized, U: CloneUnfreezable<T>> ThawElement<&Frozen<T>> for U {
+    fn thaw_element(element: &Frozen<T>) -> Self {
+        element.to_thawed()
+    }
+}
+impl<K: Freezable, V: Freezable, RK: Unfreezable<K>, RV: Unfreezable<V>>
+    ThawElement<(Frozen<K>, Frozen<V>)> for (RK, RV)
+{
+    fn thaw_element((key, value): (Frozen<K>, Frozen<V>)) -> Self {
+        (key.thaw(), value.thaw())
+    }
+}
+impl<K: Freezable, V: Freezable, RK: CloneUnfreezable<K>, RV: CloneUnfreezable<V>>
+    ThawElement<(&Frozen<K>, &Frozen<V>)> for (RK, RV)
+{
+    fn thaw_element((key, value): (&Frozen<K>, &Frozen<V>)) -> Self {
+        (key.to_thawed(), value.to_thawed())
+    }
+}
+
+/// A collection which can be built by thawing each element of the frozen
+/// collection `C`, which is either a [`Frozen`] value or a reference to one.
+///
+/// This is implemented for every [`FromElements`] collection whose elements
+/// can be thawed from those of `C`.
+pub trait FromFrozen<C>: Sized {
+    /// Build the collection from the thawed elements of `frozen`.
+    fn from_frozen(frozen: C) -> Self;
+}
+impl<T: Freezable + ?Sized, D: FromElements> FromFrozen<Frozen<T>> for D
+where
+    T::Frozen: IntoIterator,
+    D::Element: ThawElement<<T::Frozen as IntoIterator>::Item>,
+{
+    /// # Panics
+    ///
+    /// Panics if [`Frozen::verify`] would.
+    fn from_frozen(frozen: Frozen<T>) -> Self {
+        frozen.verify();
+        frozen
+            .into_inner()
+            .into_iter()
+            .map(ThawElement::thaw_element)
+            .collect()
+    }
+}
+impl<'a, T: Freezable + ?Sized, D: FromElements> FromFrozen<&'a Frozen<T>> for D
+where
+    &'a T::Frozen: IntoIterator,
+    D::Element: ThawElement<<&'a T::Frozen as IntoIterator>::Item>,
+{
+    fn from_frozen(frozen: &'a Frozen<T>) -> Self {
+        frozen
+            .0
+            .into_iter()
+            .map(ThawElement::thaw_element)
+            .collect()
+    }
+}
+
+/// Thaw a frozen collection into `D`. This is the counterpart to
+/// [`FromFrozen`], like [`Into`] is to [`From`].
+pub trait ThawInto<D> {
+    /// Thaw this collection into `D`.
+    fn thaw_into(self) -> D;
+}
+impl<C, D: FromFrozen<C>> ThawInto<D> for C {
+    fn thaw_into(self) -> D {
+        D::from_frozen(self)
+    }
+}
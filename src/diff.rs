@@ -0,0 +1,188 @@
+//! Structural diffs between frozen values.
+//!
+//! [`FrozenDiff::diff`] describes how one frozen value differs from another,
+//! and [`FrozenDiff::patch`] applies that description to the old value to
+//! rebuild the new one. Collections describe their changes entry by entry,
+//! recursing into the entries which changed; plain values are simply replaced.
+use core::time::Duration;
+
+#[cfg(feature = "alloc")]
+pub use crate::frozen_alloc::diff::{Edit, MapPatch, SeqPatch, SetPatch};
+use crate::{Freezable, Frozen};
+
+/// A frozen value which can be compared structurally with another.
+///
+/// For any `old` and `new`, `old.patch(&old.diff(new).unwrap())` must equal
+/// `new`.
+pub trait FrozenDiff: Sized {
+    /// A description of the changes between two values.
+    type Patch;
+
+    /// Describe the changes from `self` to `new`, or `None` if they are equal.
+    fn diff(&self, new: &Self) -> Option<Self::Patch>;
+
+    /// Apply the changes from `patch` to a copy of `self`.
+    ///
+    /// The result is unspecified if `patch` was not produced by diffing a
+    /// value equal to `self`.
+    #[must_use]
+    fn patch(&self, patch: &Self::Patch) -> Self;
+}
+
+macro_rules! impl_replace_diff {
+    ($impl_type:ty $(, $($lifetime_params:lifetime),*)?) => {
+        /// Patched by replacing the whole value.
+        impl $(<$($lifetime_params),*>)? FrozenDiff for $impl_type {
+            type Patch = Self;
+
+            fn diff(&self, new: &Self) -> Option<Self::Patch> {
+                (self != new).then(|| new.clone())
+            }
+
+            fn patch(&self, patch: &Self::Patch) -> Self {
+                patch.clone()
+            }
+        }
+    };
+}
+#[cfg(feature = "alloc")]
+pub(crate) use impl_replace_diff;
+
+macro_rules! impl_float_diff {
+    ($($impl_type:ty),*) => {
+        $(
+            /// Patched by replacing the whole value. Floats are compared by
+            /// their bits, so a NaN is unchanged from itself, and `-0.0`
+            /// differs from `0.0`.
+            impl FrozenDiff for $impl_type {
+                type Patch = Self;
+
+                fn diff(&self, new: &Self) -> Option<Self::Patch> {
+                    (self.to_bits() != new.to_bits()).then_some(*new)
+                }
+
+                fn patch(&self, patch: &Self::Patch) -> Self {
+                    *patch
+                }
+            }
+        )*
+    };
+}
+
+impl_replace_diff!(());
+impl_replace_diff!(bool);
+impl_replace_diff!(char);
+impl_replace_diff!(i8);
+impl_replace_diff!(i16);
+impl_replace_diff!(i32);
+impl_replace_diff!(i64);
+impl_replace_diff!(i128);
+impl_replace_diff!(isize);
+impl_replace_diff!(u8);
+impl_replace_diff!(u16);
+impl_replace_diff!(u32);
+impl_replace_diff!(u64);
+impl_replace_diff!(u128);
+impl_replace_diff!(usize);
+impl_replace_diff!(&'a str, 'a);
+impl_replace_diff!(Duration);
+impl_float_diff!(f32, f64);
+
+impl<T: Freezable + ?Sized> FrozenDiff for Frozen<T>
+where
+    T::Frozen: FrozenDiff,
+{
+    type Patch = <T::Frozen as FrozenDiff>::Patch;
+
+    fn diff(&self, new: &Self) -> Option<Self::Patch> {
+        self.0.diff(&new.0)
+    }
+
+    fn patch(&self, patch: &Self::Patch) -> Self {
+        Frozen::new(self.0.patch(patch))
+    }
+}
+
+/// The changes between two [`Option`]s.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OptionPatch<T: FrozenDiff> {
+    /// The value was added, removed or replaced wholesale.
+    Set(Option<T>),
+    /// The contained value changed.
+    Change(T::Patch),
+}
+impl<T: FrozenDiff + Clone> FrozenDiff for Option<T> {
+    type Patch = OptionPatch<T>;
+
+    fn diff(&self, new: &Self) -> Option<Self::Patch> {
+        match (self, new) {
+            (None, None) => None,
+            (Some(old), Some(new)) => old.diff(new).map(OptionPatch::Change),
+            _ => Some(OptionPatch::Set(new.clone())),
+        }
+    }
+
+    fn patch(&self, patch: &Self::Patch) -> Self {
+        match (self, patch) {
+            (_, OptionPatch::Set(new)) => new.clone(),
+            (Some(old), OptionPatch::Change(patch)) => Some(old.patch(patch)),
+            (None, OptionPatch::Change(_)) => None,
+        }
+    }
+}
+
+/// Patched element by element; unchanged elements are `None`.
+impl<T: FrozenDiff + Clone, const N: usize> FrozenDiff for [T; N] {
+    type Patch = [Option<T::Patch>; N];
+
+    fn diff(&self, new: &Self) -> Option<Self::Patch> {
+        let mut changed = false;
+        let patch = core::array::from_fn(|i| {
+            let patch = self[i].diff(&new[i]);
+            changed |= patch.is_some();
+            patch
+        });
+        changed.then_some(patch)
+    }
+
+    fn patch(&self, patch: &Self::Patch) -> Self {
+        core::array::from_fn(|i| match &patch[i] {
+            Some(patch) => self[i].patch(patch),
+            None => self[i].clone(),
+        })
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($param:ident $index:tt),*) => {
+        /// Patched field by field; unchanged fields are `None`.
+        impl<$($param: FrozenDiff + Clone),*> FrozenDiff for ($($param,)*) {
+            type Patch = ($(Option<$param::Patch>,)*);
+
+            fn diff(&self, new: &Self) -> Option<Self::Patch> {
+                let patch = ($(self.$index.diff(&new.$index),)*);
+                ($(patch.$index.is_some())||*).then_some(patch)
+            }
+
+            fn patch(&self, patch: &Self::Patch) -> Self {
+                ($(
+                    match &patch.$index {
+                        Some(patch) => self.$index.patch(patch),
+                        None => self.$index.clone(),
+                    },
+                )*)
+            }
+        }
+    };
+}
+tuple_impl!(A 0, B 1);
+tuple_impl!(A 0, B 1, C 2);
+tuple_impl!(A 0, B 1, C 2, D 3);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
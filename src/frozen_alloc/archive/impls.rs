@@ -0,0 +1,446 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+#[cfg(feature = "hashbrown")]
+use core::hash::{BuildHasher, Hash};
+use core::str;
+
+use super::{
+    read_bytes,
+    read_usize,
+    validate_seq,
+    Archive,
+    ArchiveError,
+    ArchiveKey,
+    ArchiveWriter,
+    ArchivedSlice,
+};
+#[cfg(feature = "hashbrown")]
+use super::{
+    validate_map,
+    validate_set,
+    write_map,
+    write_set,
+    ArchivedFrozenMap,
+    ArchivedFrozenSet,
+};
+#[cfg(feature = "hashbrown")]
+use crate::collections::hashbrown::{FrozenMap, FrozenSet};
+use crate::{DeepImmutable, Freezable, Frozen};
+
+/// Read `N` bytes which have been validated.
+fn read_array<const N: usize>(buf: &[u8], pos: usize) -> [u8; N] {
+    buf[pos..pos + N].try_into().unwrap()
+}
+
+macro_rules! impl_archive_num {
+    ($($num_type:ty),*) => {
+        $(
+            /// Stored little-endian.
+            impl Archive for $num_type {
+                const SIZE: usize = core::mem::size_of::<$num_type>();
+
+                type Archived<'a> = $num_type;
+
+                fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+                    writer.write_at(pos, &self.to_le_bytes());
+                }
+
+                fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+                    read_bytes(buf, pos, Self::SIZE).map(|_| ())
+                }
+
+                unsafe fn view(buf: &[u8], pos: usize) -> $num_type {
+                    <$num_type>::from_le_bytes(read_array(buf, pos))
+                }
+            }
+        )*
+    };
+}
+impl_archive_num!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+macro_rules! impl_archive_key {
+    ($($key_type:ty),*) => {
+        $(
+            impl ArchiveKey for $key_type {
+                fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+                    a.cmp(b)
+                }
+            }
+        )*
+    };
+}
+impl_archive_key!(
+    u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, usize, isize, bool, char
+);
+
+macro_rules! impl_archive_size {
+    ($size_type:ty, $stored_type:ty) => {
+        /// Stored as a 64-bit number, so archives can move between platforms.
+        impl Archive for $size_type {
+            const SIZE: usize = 8;
+
+            type Archived<'a> = $size_type;
+
+            fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+                (*self as $stored_type).write(writer, pos);
+            }
+
+            fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+                <$stored_type>::validate(buf, pos)?;
+                // SAFETY: validated above.
+                let value = unsafe { <$stored_type>::view(buf, pos) };
+                <$size_type>::try_from(value).map(|_| ()).map_err(|_| {
+                    ArchiveError::Invalid {
+                        pos,
+                        reason: "number does not fit on this platform",
+                    }
+                })
+            }
+
+            unsafe fn view(buf: &[u8], pos: usize) -> $size_type {
+                <$stored_type>::view(buf, pos) as $size_type
+            }
+        }
+    };
+}
+impl_archive_size!(usize, u64);
+impl_archive_size!(isize, i64);
+
+impl Archive for bool {
+    const SIZE: usize = 1;
+
+    type Archived<'a> = bool;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        u8::from(*self).write(writer, pos);
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        match read_bytes(buf, pos, 1)? {
+            [0 | 1] => Ok(()),
+            _ => Err(ArchiveError::Invalid {
+                pos,
+                reason: "invalid bool",
+            }),
+        }
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> bool {
+        buf[pos] == 1
+    }
+}
+
+impl Archive for char {
+    const SIZE: usize = 4;
+
+    type Archived<'a> = char;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        u32::from(*self).write(writer, pos);
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        u32::validate(buf, pos)?;
+        // SAFETY: validated above.
+        char::from_u32(unsafe { u32::view(buf, pos) })
+            .map(|_| ())
+            .ok_or(ArchiveError::Invalid {
+                pos,
+                reason: "invalid char",
+            })
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> char {
+        char::from_u32_unchecked(u32::view(buf, pos))
+    }
+}
+
+/// Stored out of line, and viewed as a borrowed `str`.
+impl Archive for str {
+    const SIZE: usize = 16;
+
+    type Archived<'a> = &'a str;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        writer.write_seq(pos, 1, self.bytes(), |writer, byte, pos| {
+            writer.write_at(pos, &[byte]);
+        });
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        let (start, len) = validate_seq(buf, pos, 1)?;
+        str::from_utf8(&buf[start..start + len])
+            .map(|_| ())
+            .map_err(|_| ArchiveError::Invalid {
+                pos: start,
+                reason: "invalid UTF-8",
+            })
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> &str {
+        let start = read_usize(buf, pos).unwrap();
+        let len = read_usize(buf, pos + 8).unwrap();
+        str::from_utf8_unchecked(&buf[start..start + len])
+    }
+}
+impl ArchiveKey for str {
+    fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+        a.cmp(b)
+    }
+}
+impl Archive for String {
+    const SIZE: usize = str::SIZE;
+
+    type Archived<'a> = &'a str;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        self.as_str().write(writer, pos);
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        str::validate(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> &str {
+        str::view(buf, pos)
+    }
+}
+impl ArchiveKey for String {
+    fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Stored out of line, and viewed as an [`ArchivedSlice`].
+impl<T: Archive> Archive for [T] {
+    const SIZE: usize = 16;
+
+    type Archived<'a> = ArchivedSlice<'a, T>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        writer.write_seq(pos, T::SIZE, self.iter(), |writer, item, pos| {
+            item.write(writer, pos);
+        });
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        let (start, len) = validate_seq(buf, pos, T::SIZE)?;
+        if T::SIZE > 0 {
+            for index in 0..len {
+                T::validate(buf, start + index * T::SIZE)?;
+            }
+        }
+        Ok(())
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> ArchivedSlice<'_, T> {
+        ArchivedSlice::new(buf, pos)
+    }
+}
+impl<T: ArchiveKey> ArchiveKey for [T] {
+    fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+        let mut b = b.iter();
+        for a in a.iter() {
+            let Some(b) = b.next() else {
+                return Ordering::Greater;
+            };
+            match T::cmp_archived(&a, &b) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+        if b.next().is_some() {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+impl<T: Archive> Archive for Vec<T> {
+    const SIZE: usize = <[T]>::SIZE;
+
+    type Archived<'a> = ArchivedSlice<'a, T>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        self.as_slice().write(writer, pos);
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        <[T]>::validate(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> ArchivedSlice<'_, T> {
+        <[T]>::view(buf, pos)
+    }
+}
+impl<T: ArchiveKey> ArchiveKey for Vec<T> {
+    fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+        <[T]>::cmp_archived(a, b)
+    }
+}
+
+/// Stored as a tag byte followed by the value, which is zeroed if absent.
+impl<T: Archive> Archive for Option<T> {
+    const SIZE: usize = 1 + T::SIZE;
+
+    type Archived<'a> = Option<T::Archived<'a>>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        if let Some(value) = self {
+            writer.write_at(pos, &[1]);
+            value.write(writer, pos + 1);
+        }
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        match read_bytes(buf, pos, 1)? {
+            [0] => read_bytes(buf, pos + 1, T::SIZE).map(|_| ()),
+            [1] => T::validate(buf, pos + 1),
+            _ => Err(ArchiveError::Invalid {
+                pos,
+                reason: "invalid Option tag",
+            }),
+        }
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> Option<T::Archived<'_>> {
+        (buf[pos] == 1).then(|| T::view(buf, pos + 1))
+    }
+}
+impl<T: ArchiveKey> ArchiveKey for Option<T> {
+    fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => T::cmp_archived(a, b),
+            _ => a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($param:ident $index:tt),*) => {
+        /// Stored as each field in turn.
+        impl<$($param: Archive),*> Archive for ($($param,)*) {
+            const SIZE: usize = 0 $(+ $param::SIZE)*;
+
+            type Archived<'a> = ($($param::Archived<'a>,)*);
+
+            #[allow(unused_assignments)]
+            fn write(&self, writer: &mut ArchiveWriter, mut pos: usize) {
+                $(
+                    self.$index.write(writer, pos);
+                    pos += $param::SIZE;
+                )*
+            }
+
+            #[allow(unused_assignments)]
+            fn validate(buf: &[u8], mut pos: usize) -> Result<(), ArchiveError> {
+                $(
+                    $param::validate(buf, pos)?;
+                    pos += $param::SIZE;
+                )*
+                Ok(())
+            }
+
+            #[allow(unused_assignments)]
+            unsafe fn view(buf: &[u8], mut pos: usize) -> Self::Archived<'_> {
+                ($(
+                    {
+                        let field = $param::view(buf, pos);
+                        pos += $param::SIZE;
+                        field
+                    },
+                )*)
+            }
+        }
+        /// Compared lexicographically.
+        impl<$($param: ArchiveKey),*> ArchiveKey for ($($param,)*) {
+            fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+                Ordering::Equal
+                    $(.then_with(|| $param::cmp_archived(&a.$index, &b.$index)))*
+            }
+        }
+    };
+}
+tuple_impl!(A 0, B 1);
+tuple_impl!(A 0, B 1, C 2);
+tuple_impl!(A 0, B 1, C 2, D 3);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+/// Stored and viewed like the frozen form.
+impl<T: Freezable + ?Sized> Archive for Frozen<T>
+where
+    T::Frozen: Archive,
+{
+    const SIZE: usize = T::Frozen::SIZE;
+
+    type Archived<'a> = <T::Frozen as Archive>::Archived<'a>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        self.0.write(writer, pos);
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        T::Frozen::validate(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> Self::Archived<'_> {
+        T::Frozen::view(buf, pos)
+    }
+}
+impl<T: Freezable + ?Sized> ArchiveKey for Frozen<T>
+where
+    T::Frozen: ArchiveKey + DeepImmutable,
+{
+    fn cmp_archived(a: &Self::Archived<'_>, b: &Self::Archived<'_>) -> Ordering {
+        T::Frozen::cmp_archived(a, b)
+    }
+}
+
+/// Stored sorted by key, and viewed as an [`ArchivedFrozenMap`].
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + ArchiveKey, V: Archive, S: BuildHasher> Archive
+    for FrozenMap<K, V, S>
+{
+    const SIZE: usize = 16;
+
+    type Archived<'a> = ArchivedFrozenMap<'a, K, V>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        write_map(writer, pos, self.iter());
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        validate_map::<K, V>(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> Self::Archived<'_> {
+        ArchivedFrozenMap::new(buf, pos)
+    }
+}
+/// Stored sorted, and viewed as an [`ArchivedFrozenSet`].
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + ArchiveKey, S: BuildHasher> Archive for FrozenSet<T, S> {
+    const SIZE: usize = 16;
+
+    type Archived<'a> = ArchivedFrozenSet<'a, T>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        write_set(writer, pos, self.iter());
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        validate_set::<T>(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> Self::Archived<'_> {
+        ArchivedFrozenSet::new(buf, pos)
+    }
+}
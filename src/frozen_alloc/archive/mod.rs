@@ -0,0 +1,485 @@
+//! Zero-copy archives of frozen values.
+//!
+//! [`to_bytes`] writes a frozen value into a single buffer. Values refer to
+//! each other by their offset from the start of the buffer rather than by
+//! pointer, so the buffer can be saved to a file, and later memory-mapped or
+//! read back in one go. [`FrozenArchive::open`] validates such a buffer once,
+//! after which the value is queried in place through views such as
+//! [`ArchivedFrozenMap`], without deserializing anything.
+//!
+//! Maps and sets are stored sorted by key and searched by bisection. Numbers
+//! are stored little-endian and unaligned, so archives can be read on any
+//! machine. An archive only records its contents, not their type: opening it
+//! as a different type than it was written with fails if the bytes are not
+//! valid for that type, but is otherwise not detected.
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use freezable::archive::{self, FrozenArchive};
+//! use freezable::prelude::*;
+//!
+//! let table: HashMap<String, u32> = [("one".into(), 1), ("two".into(), 2)].into();
+//! let bytes = archive::to_bytes(&table.freeze());
+//!
+//! let archive = FrozenArchive::<Frozen<HashMap<String, u32>>>::open(&bytes).unwrap();
+//! assert_eq!(archive.root().get(&"two"), Some(2));
+//! ```
+mod impls;
+mod view;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use core::hash::Hasher;
+use core::marker::PhantomData;
+
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) use view::{validate_map, validate_set, write_map, write_set};
+pub use view::{ArchivedFrozenMap, ArchivedFrozenSet, ArchivedSlice, Iter};
+
+use crate::fnv::Fnv;
+
+/// The bytes every archive starts with.
+const MAGIC: [u8; 4] = *b"FRZA";
+/// The version of the format written by this crate.
+const VERSION: u32 = 1;
+/// The magic bytes, version, payload length and payload checksum.
+const HEADER_LEN: usize = 24;
+
+/// A value which can be written to an archive and viewed in place.
+///
+/// Every value takes up [`SIZE`](Self::SIZE) bytes inside its parent (or at
+/// the start of the archive, for the root value). Anything of variable size
+/// is written further along the archive and referred to by its offset.
+pub trait Archive {
+    /// The number of bytes the value takes up inside its parent.
+    const SIZE: usize;
+
+    /// A view which reads the archived value in place.
+    type Archived<'a>: Copy;
+
+    /// Write the value into the `SIZE` bytes reserved at `pos`.
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize);
+
+    /// Check that the bytes at `pos` hold a valid archived value, including
+    /// everything it refers to.
+    ///
+    /// # Errors
+    ///
+    /// If the value refers to bytes outside `buf`, or is malformed.
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError>;
+
+    /// View the archived value at `pos`.
+    ///
+    /// # Safety
+    ///
+    /// [`validate`](Self::validate) must have succeeded for `buf` and `pos`.
+    unsafe fn view(buf: &[u8], pos: usize) -> Self::Archived<'_>;
+}
+
+/// A value which can be a key of an archived map or set.
+pub trait ArchiveKey: Archive + Ord {
+    /// Compare two archived values. This must agree with the [`Ord`]
+    /// implementation, which archived maps and sets are sorted by.
+    fn cmp_archived(
+        a: &Self::Archived<'_>,
+        b: &Self::Archived<'_>,
+    ) -> core::cmp::Ordering;
+}
+
+/// Builds an archive. Passed to [`Archive::write`].
+pub struct ArchiveWriter {
+    buf: Vec<u8>,
+}
+impl ArchiveWriter {
+    /// Reserve `len` zeroed bytes at the end of the archive, and return their
+    /// position.
+    pub fn reserve(&mut self, len: usize) -> usize {
+        let pos = self.buf.len();
+        self.buf.resize(pos + len, 0);
+        pos
+    }
+
+    /// Overwrite bytes which were reserved earlier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes were not reserved.
+    pub fn write_at(&mut self, pos: usize, bytes: &[u8]) {
+        self.buf[pos..pos + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Write a sequence of `items`, each taking `stride` bytes, at the end of
+    /// the archive, and refer to it from the 16 bytes reserved at `pos`.
+    /// `write` is called with each item and the position reserved for it.
+    ///
+    /// Read the sequence back with [`validate_seq`] and [`ArchivedSlice`].
+    pub fn write_seq<I>(
+        &mut self,
+        pos: usize,
+        stride: usize,
+        items: impl ExactSizeIterator<Item = I>,
+        mut write: impl FnMut(&mut Self, I, usize),
+    ) {
+        let len = items.len();
+        let start = self.reserve(len * stride);
+        self.write_at(pos, &(start as u64).to_le_bytes());
+        self.write_at(pos + 8, &(len as u64).to_le_bytes());
+        for (i, item) in items.enumerate() {
+            write(self, item, start + i * stride);
+        }
+    }
+}
+
+/// Check the sequence referred to by the 16 bytes at `pos`, as written by
+/// [`ArchiveWriter::write_seq`], and return its start and length. The items
+/// themselves are not checked.
+///
+/// # Errors
+///
+/// If the sequence is not inside `buf`, or does not come after `pos`.
+pub fn validate_seq(
+    buf: &[u8],
+    pos: usize,
+    stride: usize,
+) -> Result<(usize, usize), ArchiveError> {
+    let start = read_usize(buf, pos)?;
+    let len = read_usize(buf, pos + 8)?;
+    // Everything is written after whatever refers to it, which rules out
+    // cycles.
+    if start < pos + 16 {
+        return Err(ArchiveError::Invalid {
+            pos,
+            reason: "sequence does not come after its parent",
+        });
+    }
+    let size = len.checked_mul(stride).ok_or(ArchiveError::OutOfBounds {
+        pos: start,
+        len: usize::MAX,
+    })?;
+    read_bytes(buf, start, size)?;
+    Ok((start, len))
+}
+
+/// The bytes `pos..pos + len` of `buf`.
+///
+/// # Errors
+///
+/// If the bytes are not inside `buf`.
+pub fn read_bytes(buf: &[u8], pos: usize, len: usize) -> Result<&[u8], ArchiveError> {
+    pos.checked_add(len)
+        .and_then(|end| buf.get(pos..end))
+        .ok_or(ArchiveError::OutOfBounds { pos, len })
+}
+
+fn read_usize(buf: &[u8], pos: usize) -> Result<usize, ArchiveError> {
+    let bytes = read_bytes(buf, pos, 8)?;
+    usize::try_from(u64::from_le_bytes(bytes.try_into().unwrap()))
+        .map_err(|_| ArchiveError::OutOfBounds { pos, len: 8 })
+}
+
+/// Why an archive could not be opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArchiveError {
+    /// The buffer does not start with an archive header.
+    NotAnArchive,
+    /// The archive was written with an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The buffer is shorter than the archive it should hold.
+    Truncated {
+        /// The length of the whole archive.
+        expected: usize,
+        /// The length of the buffer.
+        actual: usize,
+    },
+    /// The contents do not match the checksum in the header.
+    ChecksumMismatch,
+    /// A value refers to bytes outside the archive.
+    OutOfBounds {
+        /// Where the bytes start.
+        pos: usize,
+        /// How many bytes were needed.
+        len: usize,
+    },
+    /// A value is not valid for the type the archive was opened as.
+    Invalid {
+        /// Where the value starts.
+        pos: usize,
+        /// What is wrong with it.
+        reason: &'static str,
+    },
+}
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::NotAnArchive => write!(f, "not a frozen archive"),
+            ArchiveError::UnsupportedVersion(version) => {
+                write!(f, "unsupported frozen archive version {version}")
+            }
+            ArchiveError::Truncated { expected, actual } => write!(
+                f,
+                "frozen archive is truncated ({actual} of {expected} bytes)"
+            ),
+            ArchiveError::ChecksumMismatch => write!(f, "frozen archive is corrupted"),
+            ArchiveError::OutOfBounds { pos, len } => write!(
+                f,
+                "frozen archive refers to {len} bytes at {pos}, outside the archive"
+            ),
+            ArchiveError::Invalid { pos, reason } => {
+                write!(f, "invalid value in frozen archive at {pos}: {reason}")
+            }
+        }
+    }
+}
+impl Error for ArchiveError {
+}
+
+/// Write `value` into a new archive.
+pub fn to_bytes<T: Archive + ?Sized>(value: &T) -> Vec<u8> {
+    let mut writer = ArchiveWriter {
+        buf: vec![0; HEADER_LEN],
+    };
+    let root = writer.reserve(T::SIZE);
+    value.write(&mut writer, root);
+    let mut buf = writer.buf;
+    let payload_len = (buf.len() - HEADER_LEN) as u64;
+    let checksum = checksum(&buf[HEADER_LEN..]);
+    buf[..4].copy_from_slice(&MAGIC);
+    buf[4..8].copy_from_slice(&VERSION.to_le_bytes());
+    buf[8..16].copy_from_slice(&payload_len.to_le_bytes());
+    buf[16..24].copy_from_slice(&checksum.to_le_bytes());
+    buf
+}
+
+fn checksum(payload: &[u8]) -> u64 {
+    let mut hasher = Fnv::default();
+    hasher.write(payload);
+    hasher.finish()
+}
+
+/// A validated archive of a `T`, borrowed from a buffer.
+pub struct FrozenArchive<'a, T: Archive + ?Sized> {
+    buf: &'a [u8],
+    marker: PhantomData<fn() -> T>,
+}
+impl<'a, T: Archive + ?Sized> FrozenArchive<'a, T> {
+    /// Check that `buf` holds an intact archive of a `T`.
+    ///
+    /// This reads the whole archive once; views of it never need to check it
+    /// again.
+    ///
+    /// # Errors
+    ///
+    /// If `buf` is not an archive, is truncated or corrupted, or does not
+    /// hold a valid `T`.
+    pub fn open(buf: &'a [u8]) -> Result<Self, ArchiveError> {
+        if buf.len() < HEADER_LEN {
+            return Err(if buf.starts_with(&MAGIC) {
+                ArchiveError::Truncated {
+                    expected: HEADER_LEN,
+                    actual: buf.len(),
+                }
+            } else {
+                ArchiveError::NotAnArchive
+            });
+        }
+        if buf[..4] != MAGIC {
+            return Err(ArchiveError::NotAnArchive);
+        }
+        let version = u32::from_le_bytes(buf[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+        let expected =
+            usize::try_from(u64::from_le_bytes(buf[8..16].try_into().unwrap()))
+                .ok()
+                .and_then(|len| len.checked_add(HEADER_LEN))
+                .ok_or(ArchiveError::Invalid {
+                    pos: 8,
+                    reason: "archive length overflows",
+                })?;
+        if buf.len() < expected {
+            return Err(ArchiveError::Truncated {
+                expected,
+                actual: buf.len(),
+            });
+        }
+        let buf = &buf[..expected];
+        if checksum(&buf[HEADER_LEN..])
+            != u64::from_le_bytes(buf[16..24].try_into().unwrap())
+        {
+            return Err(ArchiveError::ChecksumMismatch);
+        }
+        T::validate(buf, HEADER_LEN)?;
+        Ok(FrozenArchive {
+            buf,
+            marker: PhantomData,
+        })
+    }
+
+    /// View the archived value.
+    pub fn root(&self) -> T::Archived<'a> {
+        // SAFETY: `open` validated the root value.
+        unsafe { T::view(self.buf, HEADER_LEN) }
+    }
+
+    /// The bytes of the archive.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+}
+impl<T: Archive + ?Sized> Clone for FrozenArchive<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: Archive + ?Sized> Copy for FrozenArchive<'_, T> {
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    #[cfg(feature = "hashbrown")]
+    use core::hash::BuildHasherDefault;
+
+    use super::*;
+    #[cfg(feature = "hashbrown")]
+    use crate::collections::hashbrown::{FrozenMap, FrozenSet};
+
+    #[cfg(feature = "hashbrown")]
+    type Map = FrozenMap<u32, u32, BuildHasherDefault<Fnv>>;
+    #[cfg(feature = "hashbrown")]
+    type Set = FrozenSet<u32, BuildHasherDefault<Fnv>>;
+
+    /// Where the root value starts.
+    const ROOT: usize = HEADER_LEN;
+
+    /// Update the checksum after tampering with the payload, so that the
+    /// tampering is found by validation rather than by the checksum.
+    fn reseal(bytes: &mut [u8]) {
+        let checksum = checksum(&bytes[HEADER_LEN..]);
+        bytes[16..24].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    fn open<T: Archive + ?Sized>(bytes: &[u8]) -> Result<(), ArchiveError> {
+        FrozenArchive::<T>::open(bytes).map(|_| ())
+    }
+
+    #[test]
+    fn rejects_truncated_archives() {
+        let bytes = to_bytes(&vec![1u32, 2, 3]);
+        assert_eq!(open::<Vec<u32>>(&bytes), Ok(()));
+        assert_eq!(
+            open::<Vec<u32>>(&bytes[..bytes.len() - 1]),
+            Err(ArchiveError::Truncated {
+                expected: bytes.len(),
+                actual: bytes.len() - 1,
+            })
+        );
+        assert_eq!(
+            open::<Vec<u32>>(&bytes[..10]),
+            Err(ArchiveError::Truncated {
+                expected: HEADER_LEN,
+                actual: 10,
+            })
+        );
+        assert_eq!(
+            open::<Vec<u32>>(&bytes[1..]),
+            Err(ArchiveError::NotAnArchive)
+        );
+    }
+
+    #[test]
+    fn rejects_corrupted_archives() {
+        let mut bytes = to_bytes(&vec![1u32, 2, 3]);
+        *bytes.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            open::<Vec<u32>>(&bytes),
+            Err(ArchiveError::ChecksumMismatch)
+        );
+        bytes[4] = 2;
+        assert_eq!(
+            open::<Vec<u32>>(&bytes),
+            Err(ArchiveError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_references() {
+        let mut bytes = to_bytes(&vec![1u32, 2, 3]);
+        bytes[ROOT + 8..ROOT + 16].copy_from_slice(&4u64.to_le_bytes());
+        reseal(&mut bytes);
+        assert_eq!(
+            open::<Vec<u32>>(&bytes),
+            Err(ArchiveError::OutOfBounds {
+                pos: ROOT + 16,
+                len: 16,
+            })
+        );
+
+        bytes[ROOT + 8..ROOT + 16].copy_from_slice(&u64::MAX.to_le_bytes());
+        reseal(&mut bytes);
+        assert!(matches!(
+            open::<Vec<u32>>(&bytes),
+            Err(ArchiveError::OutOfBounds { .. })
+        ));
+
+        // A sequence which starts inside its parent could refer to itself.
+        let mut bytes = to_bytes(&vec![1u32, 2, 3]);
+        bytes[ROOT..ROOT + 8].copy_from_slice(&(ROOT as u64).to_le_bytes());
+        reseal(&mut bytes);
+        assert!(matches!(
+            open::<Vec<u32>>(&bytes),
+            Err(ArchiveError::Invalid { pos: ROOT, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let mut bytes = to_bytes(&String::from("ab"));
+        assert_eq!(open::<String>(&bytes), Ok(()));
+        bytes[ROOT + 16] = 0xff;
+        reseal(&mut bytes);
+        assert_eq!(
+            open::<String>(&bytes),
+            Err(ArchiveError::Invalid {
+                pos: ROOT + 16,
+                reason: "invalid UTF-8",
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "hashbrown")]
+    fn rejects_unsorted_keys() {
+        // A sequence of pairs is laid out like a map, but is not sorted.
+        let entry = |index: usize| ROOT + 16 + index * 8;
+        assert_eq!(open::<Map>(&to_bytes(&vec![(1u32, 0u32), (2, 0)])), Ok(()));
+        assert_eq!(
+            open::<Map>(&to_bytes(&vec![(2u32, 0u32), (1, 0)])),
+            Err(ArchiveError::Invalid {
+                pos: entry(1),
+                reason: "keys are not sorted and unique",
+            })
+        );
+        assert_eq!(
+            open::<Map>(&to_bytes(&vec![(1u32, 0u32), (3, 0), (3, 1)])),
+            Err(ArchiveError::Invalid {
+                pos: entry(2),
+                reason: "keys are not sorted and unique",
+            })
+        );
+
+        let element = |index: usize| ROOT + 16 + index * 4;
+        assert_eq!(open::<Set>(&to_bytes(&vec![1u32, 2, 5])), Ok(()));
+        assert_eq!(
+            open::<Set>(&to_bytes(&vec![1u32, 5, 2])),
+            Err(ArchiveError::Invalid {
+                pos: element(2),
+                reason: "keys are not sorted and unique",
+            })
+        );
+    }
+}
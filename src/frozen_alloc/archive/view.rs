@@ -0,0 +1,455 @@
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Formatter};
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use super::{read_usize, Archive, ArchiveKey};
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+use super::{validate_seq, ArchiveError, ArchiveWriter};
+
+/// A view of an archived sequence, such as a `Vec<T>` or `[T]`.
+pub struct ArchivedSlice<'a, T: ?Sized> {
+    buf: &'a [u8],
+    start: usize,
+    len: usize,
+    marker: PhantomData<fn() -> T>,
+}
+impl<'a, T: Archive> ArchivedSlice<'a, T> {
+    /// View the sequence referred to by the 16 bytes at `pos`, as written by
+    /// [`ArchiveWriter::write_seq`].
+    ///
+    /// # Safety
+    ///
+    /// The sequence must have been validated with
+    /// [`validate_seq`](super::validate_seq), and each of its items with
+    /// [`Archive::validate`].
+    pub unsafe fn new(buf: &'a [u8], pos: usize) -> Self {
+        ArchivedSlice {
+            buf,
+            start: read_usize(buf, pos).unwrap(),
+            len: read_usize(buf, pos + 8).unwrap(),
+            marker: PhantomData,
+        }
+    }
+
+    /// The number of items.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The item at `index`.
+    pub fn get(&self, index: usize) -> Option<T::Archived<'a>> {
+        // SAFETY: every item was validated along with the sequence.
+        (index < self.len)
+            .then(|| unsafe { T::view(self.buf, self.start + index * T::SIZE) })
+    }
+
+    /// The first item.
+    pub fn first(&self) -> Option<T::Archived<'a>> {
+        self.get(0)
+    }
+
+    /// The last item.
+    pub fn last(&self) -> Option<T::Archived<'a>> {
+        self.len.checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Iterate over the items.
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter {
+            slice: *self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+impl<T: ?Sized> Clone for ArchivedSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized> Copy for ArchivedSlice<'_, T> {
+}
+impl<'a, T: Archive> IntoIterator for ArchivedSlice<'a, T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = T::Archived<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T: Archive> Debug for ArchivedSlice<'a, T>
+where
+    T::Archived<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+impl<T: ArchiveKey> PartialEq for ArchivedSlice<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl<T: ArchiveKey> Eq for ArchivedSlice<'_, T> {
+}
+impl<T: ArchiveKey> PartialOrd for ArchivedSlice<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// Sequences are ordered lexicographically, like slices.
+impl<T: ArchiveKey> Ord for ArchivedSlice<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mut other_items = other.iter();
+        for item in self.iter() {
+            let Some(other_item) = other_items.next() else {
+                return Ordering::Greater;
+            };
+            match T::cmp_archived(&item, &other_item) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+        if other_items.next().is_some() {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+/// An iterator over the items of an [`ArchivedSlice`], or the entries of an
+/// archived map or set.
+pub struct Iter<'a, T: ?Sized> {
+    slice: ArchivedSlice<'a, T>,
+    front: usize,
+    back: usize,
+}
+impl<T: ?Sized> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            slice: self.slice,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+impl<'a, T: Archive> Iterator for Iter<'a, T> {
+    type Item = T::Archived<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.slice.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+impl<T: Archive> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.slice.get(self.back)
+    }
+}
+impl<T: Archive> ExactSizeIterator for Iter<'_, T> {
+}
+impl<T: Archive> FusedIterator for Iter<'_, T> {
+}
+
+/// A view of an archived map, such as a
+/// [`FrozenMap`](crate::collections::FrozenMap).
+///
+/// Entries are sorted by key, and iterate in that order.
+pub struct ArchivedFrozenMap<'a, K, V> {
+    entries: ArchivedSlice<'a, (K, V)>,
+}
+impl<'a, K: ArchiveKey, V: Archive> ArchivedFrozenMap<'a, K, V> {
+    /// View the map referred to by the 16 bytes at `pos`.
+    ///
+    /// # Safety
+    ///
+    /// The map must have been validated with [`validate_map`].
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    pub(crate) unsafe fn new(buf: &'a [u8], pos: usize) -> Self {
+        ArchivedFrozenMap {
+            entries: ArchivedSlice::new(buf, pos),
+        }
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The index of the entry for `key`, in key order.
+    pub fn get_index_of(&self, key: &K::Archived<'_>) -> Option<usize> {
+        bisect(self.len(), |index| {
+            let (entry_key, _) = self.entries.get(index).unwrap();
+            K::cmp_archived(&entry_key, key)
+        })
+    }
+
+    /// The value for `key`.
+    pub fn get(&self, key: &K::Archived<'_>) -> Option<V::Archived<'a>> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    /// The stored key and the value for `key`.
+    pub fn get_key_value(
+        &self,
+        key: &K::Archived<'_>,
+    ) -> Option<(K::Archived<'a>, V::Archived<'a>)> {
+        self.entries.get(self.get_index_of(key)?)
+    }
+
+    /// Whether the map has an entry for `key`.
+    pub fn contains_key(&self, key: &K::Archived<'_>) -> bool {
+        self.get_index_of(key).is_some()
+    }
+
+    /// The entry at `index`, in key order.
+    pub fn get_index(
+        &self,
+        index: usize,
+    ) -> Option<(K::Archived<'a>, V::Archived<'a>)> {
+        self.entries.get(index)
+    }
+
+    /// Iterate over the entries in key order.
+    pub fn iter(&self) -> Iter<'a, (K, V)> {
+        self.entries.iter()
+    }
+
+    /// Iterate over the keys in order.
+    pub fn keys(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = K::Archived<'a>> + ExactSizeIterator {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over the values in key order.
+    pub fn values(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = V::Archived<'a>> + ExactSizeIterator {
+        self.iter().map(|(_, value)| value)
+    }
+}
+impl<K, V> Clone for ArchivedFrozenMap<'_, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<K, V> Copy for ArchivedFrozenMap<'_, K, V> {
+}
+impl<'a, K: ArchiveKey, V: Archive> IntoIterator for ArchivedFrozenMap<'a, K, V> {
+    type IntoIter = Iter<'a, (K, V)>;
+    type Item = (K::Archived<'a>, V::Archived<'a>);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, K: ArchiveKey, V: Archive> Debug for ArchivedFrozenMap<'a, K, V>
+where
+    K::Archived<'a>: Debug,
+    V::Archived<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A view of an archived set, such as a
+/// [`FrozenSet`](crate::collections::FrozenSet).
+///
+/// Elements are sorted, and iterate in that order.
+pub struct ArchivedFrozenSet<'a, T> {
+    items: ArchivedSlice<'a, T>,
+}
+impl<'a, T: ArchiveKey> ArchivedFrozenSet<'a, T> {
+    /// View the set referred to by the 16 bytes at `pos`.
+    ///
+    /// # Safety
+    ///
+    /// The set must have been validated with [`validate_set`].
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    pub(crate) unsafe fn new(buf: &'a [u8], pos: usize) -> Self {
+        ArchivedFrozenSet {
+            items: ArchivedSlice::new(buf, pos),
+        }
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The index of `value`, in sorted order.
+    pub fn get_index_of(&self, value: &T::Archived<'_>) -> Option<usize> {
+        bisect(self.len(), |index| {
+            T::cmp_archived(&self.items.get(index).unwrap(), value)
+        })
+    }
+
+    /// Whether the set contains `value`.
+    pub fn contains(&self, value: &T::Archived<'_>) -> bool {
+        self.get_index_of(value).is_some()
+    }
+
+    /// The element at `index`, in sorted order.
+    pub fn get_index(&self, index: usize) -> Option<T::Archived<'a>> {
+        self.items.get(index)
+    }
+
+    /// Iterate over the elements in sorted order.
+    pub fn iter(&self) -> Iter<'a, T> {
+        self.items.iter()
+    }
+}
+impl<T> Clone for ArchivedFrozenSet<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for ArchivedFrozenSet<'_, T> {
+}
+impl<'a, T: ArchiveKey> IntoIterator for ArchivedFrozenSet<'a, T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = T::Archived<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, T: ArchiveKey> Debug for ArchivedFrozenSet<'a, T>
+where
+    T::Archived<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Find the index in `0..len` for which `cmp` returns [`Ordering::Equal`],
+/// where `cmp` orders indices below it as [`Ordering::Less`].
+fn bisect(len: usize, mut cmp: impl FnMut(usize) -> Ordering) -> Option<usize> {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match cmp(mid) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return Some(mid),
+        }
+    }
+    None
+}
+
+/// Write the entries of a map, sorted by key, and refer to them from the 16
+/// bytes at `pos`.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn write_map<'a, K: ArchiveKey + 'a, V: Archive + 'a>(
+    writer: &mut ArchiveWriter,
+    pos: usize,
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    writer.write_seq(
+        pos,
+        K::SIZE + V::SIZE,
+        entries.into_iter(),
+        |writer, (key, value), pos| {
+            key.write(writer, pos);
+            value.write(writer, pos + K::SIZE);
+        },
+    );
+}
+
+/// Check the map referred to by the 16 bytes at `pos`, including that its
+/// keys are sorted and unique.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn validate_map<K: ArchiveKey, V: Archive>(
+    buf: &[u8],
+    pos: usize,
+) -> Result<(), ArchiveError> {
+    validate_sorted::<(K, V)>(buf, pos, |a, b| K::cmp_archived(&a.0, &b.0))
+}
+
+/// Write the elements of a set, sorted, and refer to them from the 16 bytes
+/// at `pos`.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn write_set<'a, T: ArchiveKey + 'a>(
+    writer: &mut ArchiveWriter,
+    pos: usize,
+    items: impl Iterator<Item = &'a T>,
+) {
+    let mut items: Vec<_> = items.collect();
+    items.sort_unstable();
+    writer.write_seq(pos, T::SIZE, items.into_iter(), |writer, item, pos| {
+        item.write(writer, pos);
+    });
+}
+
+/// Check the set referred to by the 16 bytes at `pos`, including that its
+/// elements are sorted and unique.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn validate_set<T: ArchiveKey>(
+    buf: &[u8],
+    pos: usize,
+) -> Result<(), ArchiveError> {
+    validate_sorted::<T>(buf, pos, T::cmp_archived)
+}
+
+/// Check a sequence of `T`, which `cmp` must find strictly increasing.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+fn validate_sorted<T: Archive>(
+    buf: &[u8],
+    pos: usize,
+    cmp: impl Fn(&T::Archived<'_>, &T::Archived<'_>) -> Ordering,
+) -> Result<(), ArchiveError> {
+    let (start, len) = validate_seq(buf, pos, T::SIZE)?;
+    let mut previous = None;
+    for index in 0..len {
+        let item_pos = start + index * T::SIZE;
+        T::validate(buf, item_pos)?;
+        // SAFETY: the item was just validated.
+        let item = unsafe { T::view(buf, item_pos) };
+        if let Some(previous) = previous {
+            if cmp(&previous, &item) != Ordering::Less {
+                return Err(ArchiveError::Invalid {
+                    pos: item_pos,
+                    reason: "keys are not sorted and unique",
+                });
+            }
+        }
+        previous = Some(item);
+    }
+    Ok(())
+}
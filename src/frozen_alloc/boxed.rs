@@ -1,13 +1,13 @@
-use std::boxed::Box;
+use alloc::boxed::Box;
 
-use crate::{Freezable, Frozen, Unfreezable};
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
 
 impl<T: Freezable> Freezable for Box<T> {
     type Frozen = T::Frozen;
 
     fn freeze(self) -> Frozen<Self> {
         // Nasty hack required to satisfy the type system.
-        Frozen((*self).freeze().0)
+        (*self).freeze().rewrap()
     }
 }
 
@@ -16,3 +16,8 @@ impl<U: Unfreezable<T>, T: Freezable> Unfreezable<T> for Box<U> {
         Box::new(U::thaw(wrapped))
     }
 }
+impl<U: CloneUnfreezable<T>, T: Freezable> CloneUnfreezable<T> for Box<U> {
+    fn thaw_cloned(wrapped: &<T as Freezable>::Frozen) -> Self {
+        Box::new(U::thaw_cloned(wrapped))
+    }
+}
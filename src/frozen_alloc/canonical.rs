@@ -0,0 +1,187 @@
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+use core::fmt::Display;
+use core::fmt::{self, Debug, Formatter};
+#[cfg(feature = "hashbrown")]
+use core::hash::{BuildHasher, Hash};
+
+use crate::canonical::{impl_fmt_canonical, FmtCanonical, Options};
+#[cfg(feature = "hashbrown")]
+use crate::collections::hashbrown::{FrozenMap, FrozenSet};
+#[cfg(target_has_atomic = "ptr")]
+use crate::collections::PersistentVec;
+use crate::collections::{BitSetElement, FrozenBitSet, FrozenHeap};
+use crate::value::{FrozenStr, FrozenValue};
+
+/// Sort `items` by `key`. Items with equal keys are sorted by `format`, so
+/// the order does not depend on the order they came in.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+fn sort_by_key<T, K: Ord + ?Sized>(
+    items: &mut [T],
+    key: impl Fn(&T) -> &K,
+    format: impl Fn(&T) -> String,
+) {
+    items.sort_by(|a, b| key(a).cmp(key(b)).then_with(|| format(a).cmp(&format(b))));
+}
+
+/// Write `entries` as a map, sorted by key.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn fmt_map_sorted<
+    'a,
+    K: FmtCanonical + Ord + 'a,
+    V: FmtCanonical + ?Sized + 'a,
+>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    options: Options,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let mut entries: Vec<_> = entries.collect();
+    sort_by_key(
+        &mut entries,
+        |&(key, _)| key,
+        |&(key, value)| format!("{:?}", (options.nested(key), options.nested(value))),
+    );
+    f.debug_map()
+        .entries(
+            entries
+                .into_iter()
+                .map(|(key, value)| (options.nested(key), options.nested(value))),
+        )
+        .finish()
+}
+
+/// Write `items` as a set, sorted in ascending order.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn fmt_set_sorted<'a, T: FmtCanonical + Ord + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    options: Options,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let mut items: Vec<_> = items.collect();
+    sort_by_key(
+        &mut items,
+        |&item| item,
+        |&item| format!("{:?}", options.nested(item)),
+    );
+    f.debug_set()
+        .entries(items.into_iter().map(|item| options.nested(item)))
+        .finish()
+}
+
+/// Write `entries` with map literal syntax, in the order given.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn display_map<'a, K: Display + 'a, V: Display + 'a>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    f.write_str("{")?;
+    for (i, (key, value)) in entries.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{key}: {value}")?;
+    }
+    f.write_str("}")
+}
+
+/// Write `entries` with map literal syntax, sorted by key.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn display_map_sorted<'a, K: Display + Ord + 'a, V: Display + 'a>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let mut entries: Vec<_> = entries.collect();
+    sort_by_key(
+        &mut entries,
+        |&(key, _)| key,
+        |(key, value)| format!("{key}: {value}"),
+    );
+    display_map(entries.into_iter(), f)
+}
+
+/// Write `items` with set literal syntax, in the order given.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn display_set<'a, T: Display + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    f.write_str("{")?;
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    f.write_str("}")
+}
+
+/// Write `items` with set literal syntax, sorted in ascending order.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn display_set_sorted<'a, T: Display + Ord + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    let mut items: Vec<_> = items.collect();
+    sort_by_key(&mut items, |&item| item, |item| format!("{item}"));
+    display_set(items.into_iter(), f)
+}
+
+impl_fmt_canonical!(String, FrozenStr, FrozenValue);
+/// Bit sets are already written in ascending order.
+impl<T: BitSetElement + Debug> FmtCanonical for FrozenBitSet<T> {
+}
+impl<T: FmtCanonical> FmtCanonical for Vec<T> {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        options.nested(self.as_slice()).fmt(f)
+    }
+}
+/// Written as a list in ascending order.
+impl<T: FmtCanonical> FmtCanonical for FrozenHeap<T> {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        options.nested(self.as_sorted_slice()).fmt(f)
+    }
+}
+#[cfg(target_has_atomic = "ptr")]
+impl<T: FmtCanonical> FmtCanonical for PersistentVec<T> {
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|item| options.nested(item)))
+            .finish()
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + Ord + FmtCanonical, V: FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenMap<K, V, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_map_sorted(self.iter(), options, f)
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + Ord + FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenSet<T, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_set_sorted(self.iter(), options, f)
+    }
+}
+
+/// Written as a map literal, sorted by key.
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + Ord + Display, V: Display, S: BuildHasher> Display
+    for FrozenMap<K, V, S>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_map_sorted(self.iter(), f)
+    }
+}
+/// Written as a set literal, in ascending order.
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + Ord + Display, S: BuildHasher> Display for FrozenSet<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_set_sorted(self.iter(), f)
+    }
+}
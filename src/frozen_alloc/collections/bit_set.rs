@@ -0,0 +1,444 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+#[cfg(feature = "hashbrown")]
+use core::hash::BuildHasher;
+use core::hash::Hash;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{BitAnd, BitOr, BitXor, Sub};
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::HashSet;
+
+#[cfg(feature = "hashbrown")]
+use crate::DeepImmutable;
+use crate::{CloneUnfreezable, Freezable, FreezesToSelf, Frozen, Unfreezable};
+
+/// The number of elements each word of a [`FrozenBitSet`] holds.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// An integer type whose sets can be frozen into a [`FrozenBitSet`], with
+/// each element stored as the bit at its own index.
+pub trait BitSetElement: Copy + Ord + FreezesToSelf {
+    /// The index of this element's bit.
+    fn to_index(self) -> usize;
+
+    /// The element whose bit is at `index`, which was returned by
+    /// [`to_index`](Self::to_index).
+    fn from_index(index: usize) -> Self;
+}
+macro_rules! impl_bit_set_element {
+    ($($impl_type:ty),*) => {
+        $(
+            impl BitSetElement for $impl_type {
+                fn to_index(self) -> usize {
+                    self.into()
+                }
+
+                fn from_index(index: usize) -> Self {
+                    index as $impl_type
+                }
+            }
+        )*
+    };
+}
+impl_bit_set_element!(u8, u16);
+
+/// A set of small integers, which freezes into a [`FrozenBitSet`].
+///
+/// `C` is any collection of the elements, such as a `HashSet<u8>` or a
+/// `BTreeSet<u16>`. Duplicate elements are only stored once.
+///
+/// A `HashSet` or `BTreeSet` which is already frozen can be repacked with
+/// [`From`], as in `Frozen::<BitSet<HashSet<u8>>>::from(frozen)`.
+#[derive(Clone, Debug, Default)]
+pub struct BitSet<C>(pub C);
+impl<C> From<C> for BitSet<C> {
+    fn from(elements: C) -> Self {
+        BitSet(elements)
+    }
+}
+
+/// A frozen set of small integers, packed into an array of bits.
+///
+/// Checking whether an element is present takes constant time, and set
+/// operations work on whole words at a time. Equality, hashing and ordering
+/// are computed over the words, so they take time proportional to the
+/// largest element rather than to the number of elements. Sets are ordered by
+/// their words, which is not the order of their sorted elements.
+///
+/// Created by freezing a [`BitSet`], or by collecting an iterator.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrozenBitSet<T> {
+    /// The bit for index `i` is bit `i % 64` of word `i / 64`. There are no
+    /// trailing zero words, so equal sets have equal words.
+    pub(crate) words: Box<[u64]>,
+    marker: PhantomData<fn() -> T>,
+}
+impl<T: BitSetElement> FrozenBitSet<T> {
+    fn from_words(mut words: Vec<u64>) -> Self {
+        while words.last() == Some(&0) {
+            words.pop();
+        }
+        FrozenBitSet {
+            words: words.into_boxed_slice(),
+            marker: PhantomData,
+        }
+    }
+
+    /// The number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Whether `element` is in the set.
+    pub fn contains(&self, element: T) -> bool {
+        let index = element.to_index();
+        self.words
+            .get(index / WORD_BITS)
+            .is_some_and(|word| word & (1 << (index % WORD_BITS)) != 0)
+    }
+
+    /// The smallest element.
+    pub fn first(&self) -> Option<T> {
+        self.iter().next()
+    }
+
+    /// The largest element.
+    pub fn last(&self) -> Option<T> {
+        let word = self.words.last()?;
+        let index = (self.words.len() - 1) * WORD_BITS + (WORD_BITS - 1)
+            - word.leading_zeros() as usize;
+        Some(T::from_index(index))
+    }
+
+    /// The packed bits: element index `i` is bit `i % 64` of word `i / 64`.
+    ///
+    /// There are no trailing zero words.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Iterate over the elements in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            bits: Bits::new(&self.words),
+            marker: PhantomData,
+        }
+    }
+
+    /// The elements in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let (longer, shorter) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = longer.words.to_vec();
+        for (word, other) in words.iter_mut().zip(&*shorter.words) {
+            *word |= other;
+        }
+        Self::from_words(words)
+    }
+
+    /// The elements in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::from_words(
+            self.words
+                .iter()
+                .zip(&*other.words)
+                .map(|(a, b)| a & b)
+                .collect(),
+        )
+    }
+
+    /// The elements in this set but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut words = self.words.to_vec();
+        for (word, other) in words.iter_mut().zip(&*other.words) {
+            *word &= !other;
+        }
+        Self::from_words(words)
+    }
+
+    /// The elements in exactly one of the sets.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        let (longer, shorter) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = longer.words.to_vec();
+        for (word, other) in words.iter_mut().zip(&*shorter.words) {
+            *word ^= other;
+        }
+        Self::from_words(words)
+    }
+
+    /// Whether every element of this set is in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.len() <= other.words.len()
+            && self
+                .words
+                .iter()
+                .zip(&*other.words)
+                .all(|(a, b)| a & !b == 0)
+    }
+
+    /// Whether every element of `other` is in this set.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Whether the sets have no elements in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(&*other.words)
+            .all(|(a, b)| a & b == 0)
+    }
+}
+impl<T: BitSetElement> Default for FrozenBitSet<T> {
+    fn default() -> Self {
+        Self::from_words(Vec::new())
+    }
+}
+impl<T: BitSetElement + Debug> Debug for FrozenBitSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+impl<T: BitSetElement> FromIterator<T> for FrozenBitSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut words = Vec::new();
+        for element in iter {
+            let index = element.to_index();
+            let word = index / WORD_BITS;
+            if word >= words.len() {
+                words.resize(word + 1, 0);
+            }
+            words[word] |= 1 << (index % WORD_BITS);
+        }
+        Self::from_words(words)
+    }
+}
+impl<'a, T: BitSetElement> IntoIterator for &'a FrozenBitSet<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<T: BitSetElement> IntoIterator for FrozenBitSet<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            bits: Bits::new(self.words),
+            marker: PhantomData,
+        }
+    }
+}
+
+macro_rules! impl_set_op {
+    ($($trait_name:ident($trait_fn:ident) => $method:ident),*) => {
+        $(
+            impl<T: BitSetElement> $trait_name for &FrozenBitSet<T> {
+                type Output = FrozenBitSet<T>;
+
+                fn $trait_fn(self, rhs: Self) -> FrozenBitSet<T> {
+                    self.$method(rhs)
+                }
+            }
+        )*
+    };
+}
+impl_set_op!(
+    BitOr(bitor) => union,
+    BitAnd(bitand) => intersection,
+    BitXor(bitxor) => symmetric_difference,
+    Sub(sub) => difference
+);
+
+/// The set bits of an array of words, in ascending order.
+#[derive(Clone)]
+struct Bits<W> {
+    words: W,
+    /// The index of the next word to load into `current`.
+    next: usize,
+    /// The bits of the current word which have not been visited yet.
+    current: u64,
+    /// The number of set bits which have not been visited yet.
+    remaining: usize,
+}
+impl<W: AsRef<[u64]>> Bits<W> {
+    fn new(words: W) -> Self {
+        let remaining = words
+            .as_ref()
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        Bits {
+            words,
+            next: 0,
+            current: 0,
+            remaining,
+        }
+    }
+}
+impl<W: AsRef<[u64]>> Iterator for Bits<W> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.current = *self.words.as_ref().get(self.next)?;
+            self.next += 1;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        self.remaining -= 1;
+        Some((self.next - 1) * WORD_BITS + bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// An iterator over the elements of a [`FrozenBitSet`], in ascending order.
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    bits: Bits<&'a [u64]>,
+    marker: PhantomData<fn() -> T>,
+}
+impl<T: BitSetElement> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.bits.next().map(T::from_index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bits.size_hint()
+    }
+}
+impl<T: BitSetElement> ExactSizeIterator for Iter<'_, T> {
+}
+impl<T: BitSetElement> FusedIterator for Iter<'_, T> {
+}
+
+/// An owning iterator over the elements of a [`FrozenBitSet`], in ascending
+/// order.
+pub struct IntoIter<T> {
+    bits: Bits<Box<[u64]>>,
+    marker: PhantomData<fn() -> T>,
+}
+impl<T: BitSetElement> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.bits.next().map(T::from_index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.bits.size_hint()
+    }
+}
+impl<T: BitSetElement> ExactSizeIterator for IntoIter<T> {
+}
+impl<T: BitSetElement> FusedIterator for IntoIter<T> {
+}
+
+/// Pack the elements into a [`FrozenBitSet`].
+impl<T: BitSetElement, C: IntoIterator<Item = T>> Freezable for BitSet<C> {
+    type Frozen = FrozenBitSet<T>;
+
+    fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self.0.into_iter().collect())
+    }
+}
+/// Repack a frozen [`BTreeSet`] into a [`FrozenBitSet`].
+impl<T: BitSetElement> From<Frozen<BTreeSet<T>>> for Frozen<BitSet<BTreeSet<T>>> {
+    fn from(frozen: Frozen<BTreeSet<T>>) -> Self {
+        frozen.verify();
+        Frozen::new(
+            frozen
+                .into_inner()
+                .into_iter()
+                .map(Frozen::into_inner)
+                .collect(),
+        )
+    }
+}
+/// Repack a frozen [`HashSet`] into a [`FrozenBitSet`].
+#[cfg(feature = "hashbrown")]
+impl<T: BitSetElement + Hash + DeepImmutable, S: BuildHasher + Clone>
+    From<Frozen<HashSet<T, S>>> for Frozen<BitSet<HashSet<T, S>>>
+{
+    fn from(frozen: Frozen<HashSet<T, S>>) -> Self {
+        frozen.verify();
+        Frozen::new(
+            frozen
+                .into_inner()
+                .into_iter()
+                .map(Frozen::into_inner)
+                .collect(),
+        )
+    }
+}
+impl<T: BitSetElement, C: IntoIterator<Item = T>, D: FromIterator<T>>
+    Unfreezable<BitSet<C>> for BitSet<D>
+{
+    fn thaw(val: FrozenBitSet<T>) -> Self {
+        BitSet(val.into_iter().collect())
+    }
+}
+impl<T: BitSetElement, C: IntoIterator<Item = T>, D: FromIterator<T>>
+    CloneUnfreezable<BitSet<C>> for BitSet<D>
+{
+    fn thaw_cloned(val: &FrozenBitSet<T>) -> Self {
+        BitSet(val.iter().collect())
+    }
+}
+impl<T: BitSetElement, C: IntoIterator<Item = T>> Unfreezable<BitSet<C>>
+    for BTreeSet<T>
+{
+    fn thaw(val: FrozenBitSet<T>) -> Self {
+        val.into_iter().collect()
+    }
+}
+impl<T: BitSetElement, C: IntoIterator<Item = T>> CloneUnfreezable<BitSet<C>>
+    for BTreeSet<T>
+{
+    fn thaw_cloned(val: &FrozenBitSet<T>) -> Self {
+        val.iter().collect()
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: BitSetElement + Hash, C: IntoIterator<Item = T>, S: BuildHasher + Default>
+    Unfreezable<BitSet<C>> for HashSet<T, S>
+{
+    fn thaw(val: FrozenBitSet<T>) -> Self {
+        val.into_iter().collect()
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: BitSetElement + Hash, C: IntoIterator<Item = T>, S: BuildHasher + Default>
+    CloneUnfreezable<BitSet<C>> for HashSet<T, S>
+{
+    fn thaw_cloned(val: &FrozenBitSet<T>) -> Self {
+        val.iter().collect()
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::{Freezable, Frozen};
+
+/// A builder which freezes each element as it is pushed, and seals directly
+/// into a frozen sequence.
+///
+/// Created with `Frozen::<Vec<T>>::builder()`.
+pub struct FrozenVecBuilder<T: Freezable> {
+    vec: Vec<Frozen<T>>,
+}
+impl<T: Freezable> Frozen<Vec<T>> {
+    /// Create a builder for a frozen sequence.
+    pub fn builder() -> FrozenVecBuilder<T> {
+        FrozenVecBuilder { vec: Vec::new() }
+    }
+}
+impl<T: Freezable> FrozenVecBuilder<T> {
+    /// Reserve space for at least `capacity` elements in total.
+    #[must_use]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.reserve(capacity.saturating_sub(self.vec.len()));
+        self
+    }
+
+    /// Reserve space for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional);
+    }
+
+    /// The number of elements pushed so far.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Whether no elements have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Freeze and push an element.
+    pub fn push(&mut self, value: T) {
+        self.vec.push(value.freeze());
+    }
+
+    /// Finish building a frozen [`Vec`], without copying the elements.
+    pub fn seal(self) -> Frozen<Vec<T>> {
+        Frozen::new(self.vec)
+    }
+
+    /// Finish building a frozen [`VecDeque`], without copying the elements.
+    pub fn seal_deque(self) -> Frozen<VecDeque<T>> {
+        Frozen::new(self.vec)
+    }
+}
+impl<T: Freezable> Extend<T> for FrozenVecBuilder<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.vec.extend(iter.into_iter().map(Freezable::freeze));
+    }
+}
@@ -0,0 +1,569 @@
+/// Define a frozen hash map, its iterators, and the [`Freezable`] impls
+/// between it and the `HashMap` and `HashSet` in scope where this is invoked.
+/// The `hash_map` module in scope must have the map's iterators.
+///
+/// This lets the `std` and `hashbrown` maps share one implementation.
+///
+/// [`Freezable`]: crate::Freezable
+macro_rules! frozen_hash_map {
+    (
+        $(#[$attr:meta])*
+        pub struct FrozenMap<K, V, S $(= $default:ty)?>;
+    ) => {
+        use core::borrow::Borrow;
+        use core::cmp::Ordering;
+        use core::fmt::{self, Debug, Formatter};
+        use core::hash::{BuildHasher, Hash, Hasher};
+        use core::iter::FusedIterator;
+        use core::ops::Index;
+        use core::{ptr, slice};
+
+        use alloc::vec::Vec;
+
+        use $crate::collections::small::{self, Small, SMALL_LEN};
+        use $crate::collections::{hash_unordered, SortCache};
+        use $crate::heap_size::{HeapSize, SharedAllocations};
+        use $crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+        $(#[$attr])*
+        ///
+        /// Small maps have no hash table, so a `FrozenMap` does not dereference
+        /// to a `HashMap` as it once did. It has the same read methods itself,
+        /// and [`as_hash_map`](Self::as_hash_map) returns the table of a large
+        /// map.
+        #[repr(transparent)]
+        #[derive(Clone)]
+        pub struct FrozenMap<K: Hash + Eq, V, S: BuildHasher $(= $default)?>(
+            pub(crate) MapRepr<K, V, S>,
+        );
+
+        /// How a [`FrozenMap`] stores its entries.
+        #[derive(Clone)]
+        pub(crate) enum MapRepr<K, V, S> {
+            /// At most [`SMALL_LEN`] entries with distinct keys, along with the
+            /// hasher they are sorted by.
+            Small(Small<(K, V)>, S),
+            /// A hash table, along with its entries sorted by key once it has been
+            /// compared.
+            Large(HashMap<K, V, S>, SortCache<(*const K, *const V)>),
+        }
+        impl<K: Hash + Eq, V, S: BuildHasher> FrozenMap<K, V, S> {
+            /// Store `map`, inline if it is small enough.
+            pub(crate) fn from_map(map: HashMap<K, V, S>) -> Self
+            where
+                S: Clone,
+            {
+                if map.len() <= SMALL_LEN {
+                    let hasher = map.hasher().clone();
+                    let entries =
+                        Small::collect(map, &hasher, |(key, _)| key, |_, _| {});
+                    FrozenMap(MapRepr::Small(entries, hasher))
+                } else {
+                    FrozenMap(MapRepr::Large(map, SortCache::new()))
+                }
+            }
+
+            /// Store `entries`, keeping the first key and the last value for each
+            /// key, like [`HashMap::extend`].
+            pub(crate) fn from_entries(
+                entries: impl ExactSizeIterator<Item = (K, V)>,
+                hasher: S,
+            ) -> Self {
+                if entries.len() > SMALL_LEN {
+                    let mut map =
+                        HashMap::with_capacity_and_hasher(entries.len(), hasher);
+                    map.extend(entries);
+                    return FrozenMap(MapRepr::Large(map, SortCache::new()));
+                }
+                let entries = Small::collect(
+                    entries,
+                    &hasher,
+                    |(key, _)| key,
+                    |stored, (_, value)| stored.1 = value,
+                );
+                FrozenMap(MapRepr::Small(entries, hasher))
+            }
+
+            /// Copy the entries into a [`HashMap`].
+            pub(crate) fn to_map(&self) -> HashMap<K, V, S>
+            where
+                K: Clone,
+                V: Clone,
+                S: Clone,
+            {
+                match &self.0 {
+                    MapRepr::Small(entries, hasher) => {
+                        let mut map = HashMap::with_capacity_and_hasher(
+                            entries.as_slice().len(),
+                            hasher.clone(),
+                        );
+                        map.extend(entries.as_slice().iter().cloned());
+                        map
+                    }
+                    MapRepr::Large(map, _) => map.clone(),
+                }
+            }
+
+            /// The hash table holding the entries, or `None` if the map is small
+            /// enough to store them inline.
+            pub fn as_hash_map(&self) -> Option<&HashMap<K, V, S>> {
+                match &self.0 {
+                    MapRepr::Small(..) => None,
+                    MapRepr::Large(map, _) => Some(map),
+                }
+            }
+
+            /// The number of entries in the map.
+            pub fn len(&self) -> usize {
+                match &self.0 {
+                    MapRepr::Small(entries, _) => entries.as_slice().len(),
+                    MapRepr::Large(map, _) => map.len(),
+                }
+            }
+
+            /// Whether the map has no entries.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// The number of entries the map has room for. Maps stored inline
+            /// have room for eight.
+            pub fn capacity(&self) -> usize {
+                match &self.0 {
+                    MapRepr::Small(..) => SMALL_LEN,
+                    MapRepr::Large(map, _) => map.capacity(),
+                }
+            }
+
+            /// The map's hasher.
+            pub fn hasher(&self) -> &S {
+                match &self.0 {
+                    MapRepr::Small(_, hasher) => hasher,
+                    MapRepr::Large(map, _) => map.hasher(),
+                }
+            }
+
+            /// The stored key and the value for `key`.
+            pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+            where
+                K: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                match &self.0 {
+                    MapRepr::Small(entries, _) => entries
+                        .as_slice()
+                        .iter()
+                        .find(|(other, _)| other.borrow() == key)
+                        .map(|(key, value)| (key, value)),
+                    MapRepr::Large(map, _) => map.get_key_value(key),
+                }
+            }
+
+            /// The value for `key`.
+            pub fn get<Q>(&self, key: &Q) -> Option<&V>
+            where
+                K: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                self.get_key_value(key).map(|(_, value)| value)
+            }
+
+            /// Whether the map has an entry for `key`.
+            pub fn contains_key<Q>(&self, key: &Q) -> bool
+            where
+                K: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                self.get_key_value(key).is_some()
+            }
+
+            /// Iterate over the entries, in arbitrary order.
+            pub fn iter(&self) -> Iter<'_, K, V> {
+                Iter(match &self.0 {
+                    MapRepr::Small(entries, _) => {
+                        IterRepr::Small(entries.as_slice().iter())
+                    }
+                    MapRepr::Large(map, _) => IterRepr::Large(map.iter()),
+                })
+            }
+
+            /// Iterate over the keys, in arbitrary order.
+            pub fn keys(&self) -> Keys<'_, K, V> {
+                Keys(self.iter())
+            }
+
+            /// Iterate over the values, in arbitrary order.
+            pub fn values(&self) -> Values<'_, K, V> {
+                Values(self.iter())
+            }
+        }
+        impl<
+                K: Freezable,
+                V: Freezable,
+                RK: Hash + Eq + Unfreezable<K>,
+                RV: Unfreezable<V>,
+                S: BuildHasher + Clone,
+            > Unfreezable<HashMap<K, V, S>> for HashMap<RK, RV, S>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw(
+                val: <HashMap<K, V, S> as Freezable>::Frozen,
+            ) -> HashMap<RK, RV, S> {
+                let hasher = val.hasher().clone();
+                thaw_map(val, hasher)
+            }
+        }
+        impl<
+                K: Freezable,
+                V: Freezable,
+                RK: Hash + Eq + CloneUnfreezable<K>,
+                RV: CloneUnfreezable<V>,
+                S: BuildHasher + Clone,
+            > CloneUnfreezable<HashMap<K, V, S>> for HashMap<RK, RV, S>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw_cloned(
+                val: &<HashMap<K, V, S> as Freezable>::Frozen,
+            ) -> HashMap<RK, RV, S> {
+                let mut map =
+                    HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+                map.extend(val.iter().map(|(k, v)| (k.to_thawed(), v.to_thawed())));
+                map
+            }
+        }
+        impl<K: Hash + Eq, V, S: BuildHasher, B: Freezable> Index<B>
+            for FrozenMap<K, V, S>
+        where
+            K: Borrow<Frozen<B>>,
+            B::Frozen: Hash + Eq + DeepImmutable,
+        {
+            type Output = V;
+
+            fn index(&self, index: B) -> &Self::Output {
+                self.get(&index.freeze())
+                    .expect("key not found in FrozenMap")
+            }
+        }
+        impl<K: Hash + Eq + Debug, V: Debug, S: BuildHasher> Debug
+            for FrozenMap<K, V, S>
+        {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                struct Entries<'a, K, V>(Iter<'a, K, V>);
+                impl<K: Debug, V: Debug> Debug for Entries<'_, K, V> {
+                    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                        f.debug_map().entries(self.0.clone()).finish()
+                    }
+                }
+                f.debug_tuple("FrozenMap")
+                    .field(&Entries(self.iter()))
+                    .finish()
+            }
+        }
+        impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq
+            for FrozenMap<K, V, S>
+        {
+            fn eq(&self, other: &Self) -> bool {
+                self.len() == other.len()
+                    && self.iter().all(|(key, value)| {
+                        other.get(key).is_some_and(|other| value == other)
+                    })
+            }
+        }
+        impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for FrozenMap<K, V, S> {
+        }
+        impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator
+            for &'a FrozenMap<K, V, S>
+        {
+            type IntoIter = Iter<'a, K, V>;
+            type Item = (&'a K, &'a V);
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+        impl<K: Hash + Eq, V, S: BuildHasher> IntoIterator for FrozenMap<K, V, S> {
+            type IntoIter = IntoIter<K, V>;
+            type Item = (K, V);
+
+            fn into_iter(self) -> Self::IntoIter {
+                IntoIter(match self.0 {
+                    MapRepr::Small(entries, _) => {
+                        IntoIterRepr::Small(entries.into_iter())
+                    }
+                    MapRepr::Large(map, _) => IntoIterRepr::Large(map.into_iter()),
+                })
+            }
+        }
+
+        /// An iterator over the entries of a [`FrozenMap`].
+        pub struct Iter<'a, K, V>(IterRepr<'a, K, V>);
+        enum IterRepr<'a, K, V> {
+            Small(slice::Iter<'a, (K, V)>),
+            Large(hash_map::Iter<'a, K, V>),
+        }
+        impl<K, V> Clone for Iter<'_, K, V> {
+            fn clone(&self) -> Self {
+                Iter(match &self.0 {
+                    IterRepr::Small(items) => IterRepr::Small(items.clone()),
+                    IterRepr::Large(items) => IterRepr::Large(items.clone()),
+                })
+            }
+        }
+        impl<'a, K, V> Iterator for Iter<'a, K, V> {
+            type Item = (&'a K, &'a V);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                match &mut self.0 {
+                    IterRepr::Small(entries) => {
+                        entries.next().map(|(key, value)| (key, value))
+                    }
+                    IterRepr::Large(entries) => entries.next(),
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match &self.0 {
+                    IterRepr::Small(entries) => entries.size_hint(),
+                    IterRepr::Large(entries) => entries.size_hint(),
+                }
+            }
+        }
+        impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+        }
+        impl<K, V> FusedIterator for Iter<'_, K, V> {
+        }
+
+        /// An iterator over the keys of a [`FrozenMap`].
+        pub struct Keys<'a, K, V>(Iter<'a, K, V>);
+        impl<K, V> Clone for Keys<'_, K, V> {
+            fn clone(&self) -> Self {
+                Keys(self.0.clone())
+            }
+        }
+        impl<'a, K, V> Iterator for Keys<'a, K, V> {
+            type Item = &'a K;
+
+            fn next(&mut self) -> Option<&'a K> {
+                self.0.next().map(|(key, _)| key)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        impl<K, V> ExactSizeIterator for Keys<'_, K, V> {
+        }
+        impl<K, V> FusedIterator for Keys<'_, K, V> {
+        }
+
+        /// An iterator over the values of a [`FrozenMap`].
+        pub struct Values<'a, K, V>(Iter<'a, K, V>);
+        impl<K, V> Clone for Values<'_, K, V> {
+            fn clone(&self) -> Self {
+                Values(self.0.clone())
+            }
+        }
+        impl<'a, K, V> Iterator for Values<'a, K, V> {
+            type Item = &'a V;
+
+            fn next(&mut self) -> Option<&'a V> {
+                self.0.next().map(|(_, value)| value)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        impl<K, V> ExactSizeIterator for Values<'_, K, V> {
+        }
+        impl<K, V> FusedIterator for Values<'_, K, V> {
+        }
+
+        /// An owning iterator over the entries of a [`FrozenMap`].
+        pub struct IntoIter<K, V>(IntoIterRepr<K, V>);
+        enum IntoIterRepr<K, V> {
+            Small(small::IntoIter<(K, V)>),
+            Large(hash_map::IntoIter<K, V>),
+        }
+        impl<K, V> Iterator for IntoIter<K, V> {
+            type Item = (K, V);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                match &mut self.0 {
+                    IntoIterRepr::Small(entries) => entries.next(),
+                    IntoIterRepr::Large(entries) => entries.next(),
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match &self.0 {
+                    IntoIterRepr::Small(entries) => entries.size_hint(),
+                    IntoIterRepr::Large(entries) => entries.size_hint(),
+                }
+            }
+        }
+        impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+        }
+        impl<K, V> FusedIterator for IntoIter<K, V> {
+        }
+
+        #[allow(clippy::zero_sized_map_values)]
+        impl<K: Freezable, RT: Hash + Eq + Unfreezable<K>, S: BuildHasher + Clone>
+            Unfreezable<HashMap<K, (), S>> for HashSet<RT, S>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw(val: <HashMap<K, (), S> as Freezable>::Frozen) -> HashSet<RT, S> {
+                let mut set =
+                    HashSet::with_capacity_and_hasher(val.len(), val.hasher().clone());
+                set.extend(val.into_iter().map(|(key, _)| key.thaw()));
+                set
+            }
+        }
+        #[allow(clippy::zero_sized_map_values)]
+        impl<
+                K: Freezable,
+                RT: Hash + Eq + CloneUnfreezable<K>,
+                S: BuildHasher + Clone,
+            > CloneUnfreezable<HashMap<K, (), S>> for HashSet<RT, S>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw_cloned(
+                val: &<HashMap<K, (), S> as Freezable>::Frozen,
+            ) -> HashSet<RT, S> {
+                let mut set =
+                    HashSet::with_capacity_and_hasher(val.len(), val.hasher().clone());
+                set.extend(val.keys().map(Frozen::to_thawed));
+                set
+            }
+        }
+        impl<K: Freezable, V: Freezable, S: BuildHasher + Clone>
+            Frozen<HashMap<K, V, S>>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+        {
+            /// Unfreeze this map into a [`HashMap`] using `hasher`, rather than the
+            /// hasher it was frozen with.
+            pub fn thaw_with_hasher<RK, RV, RS>(self, hasher: RS) -> HashMap<RK, RV, RS>
+            where
+                RK: Hash + Eq + Unfreezable<K>,
+                RV: Unfreezable<V>,
+                RS: BuildHasher,
+            {
+                self.verify();
+                thaw_map(self.into_inner(), hasher)
+            }
+        }
+
+        /// Thaw every entry of a frozen map into a new map using `hasher`.
+        fn thaw_map<K: Freezable, V: Freezable, S, RK, RV, RS>(
+            map: FrozenMap<Frozen<K>, Frozen<V>, S>,
+            hasher: RS,
+        ) -> HashMap<RK, RV, RS>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+            S: BuildHasher,
+            RK: Hash + Eq + Unfreezable<K>,
+            RV: Unfreezable<V>,
+            RS: BuildHasher,
+        {
+            let mut thawed = HashMap::with_capacity_and_hasher(map.len(), hasher);
+            thawed.extend(map.into_iter().map(|(k, v)| (k.thaw(), v.thaw())));
+            thawed
+        }
+
+        /// Freeze a [`HashMap`].
+        ///
+        /// The frozen map keeps a clone of the original map's hasher.
+        ///
+        /// The map is rebuilt even when `K` and `V` freeze to themselves: unlike a
+        /// `Vec`, its table is not guaranteed to have the same layout once its
+        /// entries are wrapped in [`Frozen`], so it cannot be reused.
+        impl<K: Freezable, V: Freezable, S: BuildHasher + Clone> Freezable
+            for HashMap<K, V, S>
+        where
+            K::Frozen: Hash + Eq + DeepImmutable,
+        {
+            type Frozen = FrozenMap<Frozen<K>, Frozen<V>, S>;
+
+            fn freeze(self) -> Frozen<Self> {
+                let hasher = self.hasher().clone();
+                Frozen::new(FrozenMap::from_entries(
+                    self.into_iter().map(|(k, v)| (k.freeze(), v.freeze())),
+                    hasher,
+                ))
+            }
+        }
+
+        impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for FrozenMap<K, V, S> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_unordered(self.iter(), state);
+            }
+        }
+
+        impl<K: Hash + Eq + Ord, V, S: BuildHasher> FrozenMap<K, V, S> {
+            /// Call `f` with the entries, sorted by key. Small maps sort them on the
+            /// stack, and large maps sort them the first time they are compared.
+            fn with_sorted<'a, R>(
+                &'a self,
+                f: impl FnOnce(&mut dyn Iterator<Item = (&'a K, &'a V)>) -> R,
+            ) -> R {
+                match &self.0 {
+                    MapRepr::Small(entries, _) => entries.with_sorted(
+                        |(key, _)| key,
+                        |sorted| f(&mut sorted.iter().map(|(key, value)| (key, value))),
+                    ),
+                    MapRepr::Large(map, cache) => {
+                        let sorted = cache.get_or_init(|| {
+                            let mut entries: Vec<_> = map.iter().collect();
+                            entries.sort_unstable_by_key(|&(key, _)| key);
+                            entries
+                                .into_iter()
+                                .map(|(key, value)| {
+                                    (ptr::from_ref(key), ptr::from_ref(value))
+                                })
+                                .collect()
+                        });
+                        // SAFETY: the pointers are to the entries of `map`, which is
+                        // borrowed for as long as they are used.
+                        f(&mut sorted
+                            .iter()
+                            .map(|&(key, value)| unsafe { (&*key, &*value) }))
+                    }
+                }
+            }
+        }
+
+        /// Maps are ordered by comparing their entries, sorted by key,
+        /// lexicographically.
+        impl<K: Hash + Eq + Ord, V: PartialOrd, S: BuildHasher> PartialOrd
+            for FrozenMap<K, V, S>
+        {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                self.with_sorted(|a| other.with_sorted(|b| Iterator::partial_cmp(a, b)))
+            }
+        }
+        impl<K: Hash + Eq + Ord, V: Ord, S: BuildHasher> Ord for FrozenMap<K, V, S> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.with_sorted(|a| other.with_sorted(|b| Iterator::cmp(a, b)))
+            }
+        }
+
+        impl<K: Hash + Eq + HeapSize, V: HeapSize, S: BuildHasher> HeapSize
+            for FrozenMap<K, V, S>
+        {
+            fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+                match &self.0 {
+                    MapRepr::Small(entries, _) => {
+                        entries.as_slice().heap_size_with(shared)
+                    }
+                    MapRepr::Large(map, _) => map.heap_size_with(shared),
+                }
+            }
+        }
+    };
+}
+pub(crate) use frozen_hash_map;
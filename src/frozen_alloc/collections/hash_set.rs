@@ -0,0 +1,718 @@
+/// Define a frozen hash set, its iterators, and the [`Freezable`] impls
+/// between it and the `HashSet` and `HashMap` in scope where this is invoked.
+/// The `hash_set` module in scope must have the set's iterators.
+///
+/// This lets the `std` and `hashbrown` sets share one implementation.
+///
+/// [`Freezable`]: crate::Freezable
+macro_rules! frozen_hash_set {
+    (
+        $(#[$attr:meta])*
+        pub struct FrozenSet<T, S $(= $default:ty)?>;
+    ) => {
+        use core::borrow::Borrow;
+        use core::cmp::Ordering;
+        use core::fmt::{self, Debug, Formatter};
+        use core::hash::{BuildHasher, Hash, Hasher};
+        use core::iter::FusedIterator;
+        use core::ptr::{self, NonNull};
+        use core::{iter, mem, slice};
+
+        use alloc::vec::Vec;
+
+        use $crate::collections::small::{self, Small, SMALL_LEN};
+        use $crate::collections::{hash_unordered, SortCache};
+        use $crate::heap_size::{HeapSize, SharedAllocations};
+        use $crate::verify::StableAddress;
+        use $crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+        $(#[$attr])*
+        ///
+        /// Small sets have no hash table, so a `FrozenSet` does not dereference
+        /// to a `HashSet` as it once did. It has the same read methods itself,
+        /// whose set operations accept a `FrozenSet` or a `HashSet`, and
+        /// [`as_hash_set`](Self::as_hash_set) returns the table of a large set.
+        #[repr(transparent)]
+        #[derive(Clone)]
+        pub struct FrozenSet<T: Hash + Eq, S: BuildHasher $(= $default)?>(
+            pub(crate) SetRepr<T, S>,
+        );
+
+        /// How a [`FrozenSet`] stores its elements.
+        #[derive(Clone)]
+        pub(crate) enum SetRepr<T, S> {
+            /// At most [`SMALL_LEN`] distinct elements, along with the hasher they
+            /// are sorted by.
+            Small(Small<T>, S),
+            /// A hash table, along with its elements sorted once it has been
+            /// compared.
+            Large(HashSet<T, S>, SortCache<*const T>),
+        }
+        impl<T: Hash + Eq, S: BuildHasher> FrozenSet<T, S> {
+            /// Store `set`, inline if it is small enough.
+            pub(crate) fn from_set(set: HashSet<T, S>) -> Self
+            where
+                S: Clone,
+            {
+                if set.len() <= SMALL_LEN {
+                    let hasher = set.hasher().clone();
+                    let elements =
+                        Small::collect(set, &hasher, |element| element, |_, _| {});
+                    FrozenSet(SetRepr::Small(elements, hasher))
+                } else {
+                    FrozenSet(SetRepr::Large(set, SortCache::new()))
+                }
+            }
+
+            /// Store `elements`, keeping the first of any equal elements, like
+            /// [`HashSet::extend`].
+            pub(crate) fn from_elements(
+                elements: impl ExactSizeIterator<Item = T>,
+                hasher: S,
+            ) -> Self {
+                if elements.len() > SMALL_LEN {
+                    let mut set =
+                        HashSet::with_capacity_and_hasher(elements.len(), hasher);
+                    set.extend(elements);
+                    return FrozenSet(SetRepr::Large(set, SortCache::new()));
+                }
+                let elements =
+                    Small::collect(elements, &hasher, |element| element, |_, _| {});
+                FrozenSet(SetRepr::Small(elements, hasher))
+            }
+
+            /// Copy the elements into a [`HashSet`].
+            pub(crate) fn to_set(&self) -> HashSet<T, S>
+            where
+                T: Clone,
+                S: Clone,
+            {
+                match &self.0 {
+                    SetRepr::Small(elements, hasher) => {
+                        let elements = elements.as_slice();
+                        let mut set = HashSet::with_capacity_and_hasher(
+                            elements.len(),
+                            hasher.clone(),
+                        );
+                        set.extend(elements.iter().cloned());
+                        set
+                    }
+                    SetRepr::Large(set, _) => set.clone(),
+                }
+            }
+
+            /// The hash table holding the elements, or `None` if the set is small
+            /// enough to store them inline.
+            pub fn as_hash_set(&self) -> Option<&HashSet<T, S>> {
+                match &self.0 {
+                    SetRepr::Small(..) => None,
+                    SetRepr::Large(set, _) => Some(set),
+                }
+            }
+
+            /// The number of elements in the set.
+            pub fn len(&self) -> usize {
+                match &self.0 {
+                    SetRepr::Small(elements, _) => elements.as_slice().len(),
+                    SetRepr::Large(set, _) => set.len(),
+                }
+            }
+
+            /// Whether the set has no elements.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// The number of elements the set has room for. Sets stored inline
+            /// have room for eight.
+            pub fn capacity(&self) -> usize {
+                match &self.0 {
+                    SetRepr::Small(..) => SMALL_LEN,
+                    SetRepr::Large(set, _) => set.capacity(),
+                }
+            }
+
+            /// The set's hasher.
+            pub fn hasher(&self) -> &S {
+                match &self.0 {
+                    SetRepr::Small(_, hasher) => hasher,
+                    SetRepr::Large(set, _) => set.hasher(),
+                }
+            }
+
+            /// The stored element equal to `value`.
+            pub fn get<Q>(&self, value: &Q) -> Option<&T>
+            where
+                T: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                match &self.0 {
+                    SetRepr::Small(elements, _) => elements
+                        .as_slice()
+                        .iter()
+                        .find(|element| (*element).borrow() == value),
+                    SetRepr::Large(set, _) => set.get(value),
+                }
+            }
+
+            /// Whether `value` is in the set.
+            pub fn contains<Q>(&self, value: &Q) -> bool
+            where
+                T: Borrow<Q>,
+                Q: Hash + Eq + ?Sized,
+            {
+                self.get(value).is_some()
+            }
+
+            /// Iterate over the elements, in arbitrary order.
+            pub fn iter(&self) -> Iter<'_, T> {
+                Iter(match &self.0 {
+                    SetRepr::Small(elements, _) => {
+                        IterRepr::Small(elements.as_slice().iter())
+                    }
+                    SetRepr::Large(set, _) => IterRepr::Large(set.iter()),
+                })
+            }
+
+            /// The elements in this set but not in `other`.
+            pub fn difference<'a>(
+                &'a self,
+                other: impl Into<SetRef<'a, T, S>>,
+            ) -> Difference<'a, T, S> {
+                Difference {
+                    elements: self.iter(),
+                    other: other.into(),
+                }
+            }
+
+            /// The elements in both sets.
+            pub fn intersection<'a>(
+                &'a self,
+                other: impl Into<SetRef<'a, T, S>>,
+            ) -> Intersection<'a, T, S> {
+                let (this, other) = (SetRef::from(self), other.into());
+                let (smaller, larger) = if this.len() <= other.len() {
+                    (this, other)
+                } else {
+                    (other, this)
+                };
+                Intersection {
+                    elements: smaller.iter(),
+                    other: larger,
+                }
+            }
+
+            /// The elements in either set.
+            pub fn union<'a>(
+                &'a self,
+                other: impl Into<SetRef<'a, T, S>>,
+            ) -> Union<'a, T, S> {
+                let other = other.into();
+                Union(self.iter().chain(Difference {
+                    elements: other.iter(),
+                    other: SetRef::from(self),
+                }))
+            }
+
+            /// The elements in exactly one of the sets.
+            pub fn symmetric_difference<'a>(
+                &'a self,
+                other: impl Into<SetRef<'a, T, S>>,
+            ) -> SymmetricDifference<'a, T, S> {
+                let other = other.into();
+                SymmetricDifference(self.difference(other).chain(Difference {
+                    elements: other.iter(),
+                    other: SetRef::from(self),
+                }))
+            }
+
+            /// Whether every element of this set is in `other`.
+            pub fn is_subset<'a>(&'a self, other: impl Into<SetRef<'a, T, S>>) -> bool {
+                let other = other.into();
+                self.len() <= other.len()
+                    && self.iter().all(|element| other.contains(element))
+            }
+
+            /// Whether every element of `other` is in this set.
+            pub fn is_superset<'a>(
+                &'a self,
+                other: impl Into<SetRef<'a, T, S>>,
+            ) -> bool {
+                let other = other.into();
+                other.len() <= self.len()
+                    && other.iter().all(|element| self.contains(element))
+            }
+
+            /// Whether the sets have no elements in common.
+            pub fn is_disjoint<'a>(
+                &'a self,
+                other: impl Into<SetRef<'a, T, S>>,
+            ) -> bool {
+                self.intersection(other).next().is_none()
+            }
+        }
+        impl<T: Freezable, RK: Hash + Eq + Unfreezable<T>, S: BuildHasher + Clone>
+            Unfreezable<HashSet<T, S>> for HashSet<RK, S>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw(val: <HashSet<T, S> as Freezable>::Frozen) -> HashSet<RK, S> {
+                let hasher = val.hasher().clone();
+                thaw_set(val, hasher)
+            }
+        }
+        impl<
+                T: Freezable,
+                RK: Hash + Eq + CloneUnfreezable<T>,
+                S: BuildHasher + Clone,
+            > CloneUnfreezable<HashSet<T, S>> for HashSet<RK, S>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw_cloned(
+                val: &<HashSet<T, S> as Freezable>::Frozen,
+            ) -> HashSet<RK, S> {
+                let mut set =
+                    HashSet::with_capacity_and_hasher(val.len(), val.hasher().clone());
+                set.extend(val.iter().map(Frozen::to_thawed));
+                set
+            }
+        }
+        impl<T: Hash + Eq + Debug, S: BuildHasher> Debug for FrozenSet<T, S> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                struct Elements<'a, T>(Iter<'a, T>);
+                impl<T: Debug> Debug for Elements<'_, T> {
+                    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                        f.debug_set().entries(self.0.clone()).finish()
+                    }
+                }
+                f.debug_tuple("FrozenSet")
+                    .field(&Elements(self.iter()))
+                    .finish()
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> PartialEq for FrozenSet<T, S> {
+            fn eq(&self, other: &Self) -> bool {
+                self.len() == other.len() && self.is_subset(other)
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Eq for FrozenSet<T, S> {
+        }
+        impl<'a, K: Hash + Eq, S: BuildHasher> IntoIterator for &'a FrozenSet<K, S> {
+            type IntoIter = Iter<'a, K>;
+            type Item = &'a K;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+        impl<K: Hash + Eq, S: BuildHasher> IntoIterator for FrozenSet<K, S> {
+            type IntoIter = IntoIter<K>;
+            type Item = K;
+
+            fn into_iter(self) -> Self::IntoIter {
+                IntoIter(match self.0 {
+                    SetRepr::Small(elements, _) => {
+                        IntoIterRepr::Small(elements.into_iter())
+                    }
+                    SetRepr::Large(set, _) => IntoIterRepr::Large(set.into_iter()),
+                })
+            }
+        }
+
+        /// An iterator over the elements of a [`FrozenSet`].
+        pub struct Iter<'a, T>(IterRepr<'a, T>);
+        enum IterRepr<'a, T> {
+            Small(slice::Iter<'a, T>),
+            Large(hash_set::Iter<'a, T>),
+        }
+        impl<T> Clone for Iter<'_, T> {
+            fn clone(&self) -> Self {
+                Iter(match &self.0 {
+                    IterRepr::Small(items) => IterRepr::Small(items.clone()),
+                    IterRepr::Large(items) => IterRepr::Large(items.clone()),
+                })
+            }
+        }
+        impl<'a, T> Iterator for Iter<'a, T> {
+            type Item = &'a T;
+
+            fn next(&mut self) -> Option<&'a T> {
+                match &mut self.0 {
+                    IterRepr::Small(elements) => elements.next(),
+                    IterRepr::Large(elements) => elements.next(),
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match &self.0 {
+                    IterRepr::Small(elements) => elements.size_hint(),
+                    IterRepr::Large(elements) => elements.size_hint(),
+                }
+            }
+        }
+        impl<T> ExactSizeIterator for Iter<'_, T> {
+        }
+        impl<T> FusedIterator for Iter<'_, T> {
+        }
+
+        /// The other operand of a [`FrozenSet`]'s set operations: another
+        /// `FrozenSet`, or a [`HashSet`].
+        pub struct SetRef<'a, T: Hash + Eq, S: BuildHasher>(SetRefRepr<'a, T, S>);
+        enum SetRefRepr<'a, T: Hash + Eq, S: BuildHasher> {
+            Frozen(&'a FrozenSet<T, S>),
+            Hash(&'a HashSet<T, S>),
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> SetRef<'a, T, S> {
+            fn len(&self) -> usize {
+                match self.0 {
+                    SetRefRepr::Frozen(set) => set.len(),
+                    SetRefRepr::Hash(set) => set.len(),
+                }
+            }
+
+            fn contains(&self, value: &T) -> bool {
+                match self.0 {
+                    SetRefRepr::Frozen(set) => set.contains(value),
+                    SetRefRepr::Hash(set) => set.contains(value),
+                }
+            }
+
+            fn iter(&self) -> Iter<'a, T> {
+                match self.0 {
+                    SetRefRepr::Frozen(set) => set.iter(),
+                    SetRefRepr::Hash(set) => Iter(IterRepr::Large(set.iter())),
+                }
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Clone for SetRef<'_, T, S> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Copy for SetRef<'_, T, S> {
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Clone for SetRefRepr<'_, T, S> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Copy for SetRefRepr<'_, T, S> {
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> From<&'a FrozenSet<T, S>>
+            for SetRef<'a, T, S>
+        {
+            fn from(set: &'a FrozenSet<T, S>) -> Self {
+                SetRef(SetRefRepr::Frozen(set))
+            }
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> From<&'a HashSet<T, S>>
+            for SetRef<'a, T, S>
+        {
+            fn from(set: &'a HashSet<T, S>) -> Self {
+                SetRef(SetRefRepr::Hash(set))
+            }
+        }
+        impl<'a, T: Freezable, S: BuildHasher + Clone> From<&'a Frozen<HashSet<T, S>>>
+            for SetRef<'a, Frozen<T>, S>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn from(set: &'a Frozen<HashSet<T, S>>) -> Self {
+                SetRef(SetRefRepr::Frozen(set))
+            }
+        }
+
+        /// An iterator over the elements of a [`FrozenSet`] which are not in
+        /// another set.
+        pub struct Difference<'a, T: Hash + Eq, S: BuildHasher> {
+            elements: Iter<'a, T>,
+            other: SetRef<'a, T, S>,
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Clone for Difference<'_, T, S> {
+            fn clone(&self) -> Self {
+                Difference {
+                    elements: self.elements.clone(),
+                    other: self.other,
+                }
+            }
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Difference<'a, T, S> {
+            type Item = &'a T;
+
+            fn next(&mut self) -> Option<&'a T> {
+                let other = self.other;
+                self.elements.find(|element| !other.contains(element))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (0, self.elements.size_hint().1)
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> FusedIterator for Difference<'_, T, S> {
+        }
+
+        /// An iterator over the elements of a [`FrozenSet`] which are also in
+        /// another set.
+        pub struct Intersection<'a, T: Hash + Eq, S: BuildHasher> {
+            elements: Iter<'a, T>,
+            other: SetRef<'a, T, S>,
+        }
+        impl<T: Hash + Eq, S: BuildHasher> Clone for Intersection<'_, T, S> {
+            fn clone(&self) -> Self {
+                Intersection {
+                    elements: self.elements.clone(),
+                    other: self.other,
+                }
+            }
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Intersection<'a, T, S> {
+            type Item = &'a T;
+
+            fn next(&mut self) -> Option<&'a T> {
+                let other = self.other;
+                self.elements.find(|element| other.contains(element))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (0, self.elements.size_hint().1)
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> FusedIterator for Intersection<'_, T, S> {
+        }
+
+        /// An iterator over the elements of a [`FrozenSet`] and another set,
+        /// without duplicates.
+        pub struct Union<'a, T: Hash + Eq, S: BuildHasher>(
+            iter::Chain<Iter<'a, T>, Difference<'a, T, S>>,
+        );
+        impl<T: Hash + Eq, S: BuildHasher> Clone for Union<'_, T, S> {
+            fn clone(&self) -> Self {
+                Union(self.0.clone())
+            }
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Union<'a, T, S> {
+            type Item = &'a T;
+
+            fn next(&mut self) -> Option<&'a T> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> FusedIterator for Union<'_, T, S> {
+        }
+
+        /// An iterator over the elements which are in exactly one of a
+        /// [`FrozenSet`] and another set.
+        pub struct SymmetricDifference<'a, T: Hash + Eq, S: BuildHasher>(
+            iter::Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+        );
+        impl<T: Hash + Eq, S: BuildHasher> Clone for SymmetricDifference<'_, T, S> {
+            fn clone(&self) -> Self {
+                SymmetricDifference(self.0.clone())
+            }
+        }
+        impl<'a, T: Hash + Eq, S: BuildHasher> Iterator
+            for SymmetricDifference<'a, T, S>
+        {
+            type Item = &'a T;
+
+            fn next(&mut self) -> Option<&'a T> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        impl<T: Hash + Eq, S: BuildHasher> FusedIterator
+            for SymmetricDifference<'_, T, S>
+        {
+        }
+
+        /// An owning iterator over the elements of a [`FrozenSet`].
+        pub struct IntoIter<T>(IntoIterRepr<T>);
+        enum IntoIterRepr<T> {
+            Small(small::IntoIter<T>),
+            Large(hash_set::IntoIter<T>),
+        }
+        impl<T> Iterator for IntoIter<T> {
+            type Item = T;
+
+            fn next(&mut self) -> Option<T> {
+                match &mut self.0 {
+                    IntoIterRepr::Small(elements) => elements.next(),
+                    IntoIterRepr::Large(elements) => elements.next(),
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match &self.0 {
+                    IntoIterRepr::Small(elements) => elements.size_hint(),
+                    IntoIterRepr::Large(elements) => elements.size_hint(),
+                }
+            }
+        }
+        impl<T> ExactSizeIterator for IntoIter<T> {
+        }
+        impl<T> FusedIterator for IntoIter<T> {
+        }
+
+        #[allow(clippy::zero_sized_map_values)]
+        impl<T: Freezable, RK: Hash + Eq + Unfreezable<T>, S: BuildHasher + Clone>
+            Unfreezable<HashSet<T, S>> for HashMap<RK, (), S>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw(val: <HashSet<T, S> as Freezable>::Frozen) -> HashMap<RK, (), S> {
+                let mut map =
+                    HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+                map.extend(val.into_iter().map(|val| (val.thaw(), ())));
+                map
+            }
+        }
+        #[allow(clippy::zero_sized_map_values)]
+        impl<
+                T: Freezable,
+                RK: Hash + Eq + CloneUnfreezable<T>,
+                S: BuildHasher + Clone,
+            > CloneUnfreezable<HashSet<T, S>> for HashMap<RK, (), S>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            fn thaw_cloned(
+                val: &<HashSet<T, S> as Freezable>::Frozen,
+            ) -> HashMap<RK, (), S> {
+                let mut map =
+                    HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+                map.extend(val.iter().map(|val| (val.to_thawed(), ())));
+                map
+            }
+        }
+        impl<T: Freezable, S: BuildHasher + Clone> Frozen<HashSet<T, S>>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            /// Unfreeze this set into a [`HashSet`] using `hasher`, rather than the
+            /// hasher it was frozen with.
+            pub fn thaw_with_hasher<RT, RS>(self, hasher: RS) -> HashSet<RT, RS>
+            where
+                RT: Hash + Eq + Unfreezable<T>,
+                RS: BuildHasher,
+            {
+                self.verify();
+                thaw_set(self.into_inner(), hasher)
+            }
+        }
+
+        /// Thaw every element of a frozen set into a new set using `hasher`.
+        fn thaw_set<T: Freezable, S, RT, RS>(
+            set: FrozenSet<Frozen<T>, S>,
+            hasher: RS,
+        ) -> HashSet<RT, RS>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+            S: BuildHasher,
+            RT: Hash + Eq + Unfreezable<T>,
+            RS: BuildHasher,
+        {
+            let mut thawed = HashSet::with_capacity_and_hasher(set.len(), hasher);
+            thawed.extend(set.into_iter().map(Frozen::thaw));
+            thawed
+        }
+
+        /// Freeze a [`HashSet`].
+        ///
+        /// The frozen set keeps a clone of the original set's hasher.
+        ///
+        /// The set is rebuilt even when `T` freezes to itself: unlike a
+        /// `Vec`, its table is not guaranteed to have the same layout once its
+        /// elements are wrapped in [`Frozen`], so it cannot be reused.
+        impl<T: Freezable, S: BuildHasher + Clone> Freezable for HashSet<T, S>
+        where
+            T::Frozen: Hash + Eq + DeepImmutable,
+        {
+            type Frozen = FrozenSet<Frozen<T>, S>;
+
+            fn freeze(self) -> Frozen<Self> {
+                let hasher = self.hasher().clone();
+                Frozen::new_verified(FrozenSet::from_elements(
+                    self.into_iter().map(Freezable::freeze),
+                    hasher,
+                ))
+            }
+        }
+
+        impl<T: Hash + Eq, S: BuildHasher> Hash for FrozenSet<T, S> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                hash_unordered(self.iter(), state);
+            }
+        }
+
+        /// Only large sets have an address, as small sets are stored inline.
+        impl<T: Hash + Eq, S: BuildHasher> StableAddress for FrozenSet<T, S> {
+            fn stable_address(&self) -> Option<NonNull<()>> {
+                match &self.0 {
+                    SetRepr::Large(set, _) if mem::size_of::<T>() != 0 => set
+                        .iter()
+                        .next()
+                        .map(|element| NonNull::from(element).cast()),
+                    _ => None,
+                }
+            }
+        }
+
+        impl<T: Hash + Eq + Ord, S: BuildHasher> FrozenSet<T, S> {
+            /// Call `f` with the elements, sorted. Small sets sort them on the stack,
+            /// and large sets sort them the first time they are compared.
+            fn with_sorted<'a, R>(
+                &'a self,
+                f: impl FnOnce(&mut dyn Iterator<Item = &'a T>) -> R,
+            ) -> R {
+                match &self.0 {
+                    SetRepr::Small(elements, _) => elements.with_sorted(
+                        |element| element,
+                        |sorted| f(&mut sorted.iter().copied()),
+                    ),
+                    SetRepr::Large(set, cache) => {
+                        let sorted = cache.get_or_init(|| {
+                            let mut elements: Vec<_> = set.iter().collect();
+                            elements.sort_unstable();
+                            elements.into_iter().map(ptr::from_ref).collect()
+                        });
+                        // SAFETY: the pointers are to the elements of `set`, which is
+                        // borrowed for as long as they are used.
+                        f(&mut sorted.iter().map(|&element| unsafe { &*element }))
+                    }
+                }
+            }
+        }
+
+        /// Sets are ordered by comparing their sorted elements lexicographically.
+        impl<T: Hash + Eq + Ord, S: BuildHasher> PartialOrd for FrozenSet<T, S> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl<T: Hash + Eq + Ord, S: BuildHasher> Ord for FrozenSet<T, S> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.with_sorted(|a| other.with_sorted(|b| Iterator::cmp(a, b)))
+            }
+        }
+
+        impl<T: Hash + Eq + HeapSize, S: BuildHasher> HeapSize for FrozenSet<T, S> {
+            fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+                match &self.0 {
+                    SetRepr::Small(elements, _) => {
+                        elements.as_slice().heap_size_with(shared)
+                    }
+                    SetRepr::Large(set, _) => set.heap_size_with(shared),
+                }
+            }
+        }
+    };
+}
+pub(crate) use frozen_hash_set;
@@ -0,0 +1,13 @@
+use ::hashbrown::{hash_map, HashMap, HashSet};
+
+use crate::frozen_alloc::collections::hash_map::frozen_hash_map;
+
+frozen_hash_map! {
+    /// A frozen [`hashbrown::HashMap`](HashMap).
+    ///
+    /// Unlike the `std` [`FrozenMap`](crate::collections::FrozenMap), this has
+    /// no default hasher, as `no_std` targets have no source of random seeds.
+    /// Like it, maps with at most eight entries are stored inline rather than
+    /// in a hash table.
+    pub struct FrozenMap<K, V, S>;
+}
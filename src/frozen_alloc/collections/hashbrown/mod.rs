@@ -0,0 +1,6 @@
+//! Frozen forms of the [`hashbrown`](::hashbrown) hash map and set, which are
+//! available without `std`.
+mod map;
+mod set;
+pub use map::FrozenMap;
+pub use set::FrozenSet;
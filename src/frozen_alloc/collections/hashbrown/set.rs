@@ -0,0 +1,13 @@
+use ::hashbrown::{hash_set, HashMap, HashSet};
+
+use crate::frozen_alloc::collections::hash_set::frozen_hash_set;
+
+frozen_hash_set! {
+    /// A frozen [`hashbrown::HashSet`](HashSet).
+    ///
+    /// Unlike the `std` [`FrozenSet`](crate::collections::FrozenSet), this has
+    /// no default hasher, as `no_std` targets have no source of random seeds.
+    /// Like it, sets with at most eight elements are stored inline rather than
+    /// in a hash table.
+    pub struct FrozenSet<T, S>;
+}
@@ -0,0 +1,169 @@
+use alloc::collections::BinaryHeap;
+use alloc::vec::{self, Vec};
+use core::iter::{FusedIterator, Rev};
+use core::slice;
+
+use super::ordered::{freeze_vec, thaw_vec};
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
+
+/// A frozen priority queue.
+///
+/// This is the frozen form of a [`BinaryHeap`]. The elements are kept fully
+/// sorted, so besides [`peek`](Self::peek)ing at the greatest element, the
+/// whole queue can be iterated in priority order from either end.
+///
+/// Equality, hashing and ordering are those of the sorted elements, so two
+/// heaps holding the same elements are equal however they were built.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrozenHeap<T>(pub(crate) Vec<T>);
+impl<T> FrozenHeap<T> {
+    /// The number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The greatest element.
+    pub fn peek(&self) -> Option<&T> {
+        self.0.last()
+    }
+
+    /// Iterate over the `k` greatest elements, greatest first.
+    pub fn top_k(&self, k: usize) -> Iter<'_, T> {
+        Iter(self.0[self.0.len().saturating_sub(k)..].iter().rev())
+    }
+
+    /// Iterate over the elements, greatest first.
+    ///
+    /// Reverse the iterator to visit them smallest first.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.iter().rev())
+    }
+
+    /// The elements in ascending order.
+    pub fn as_sorted_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Take the elements out in ascending order, like
+    /// [`BinaryHeap::into_sorted_vec`].
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+impl<'a, T> IntoIterator for &'a FrozenHeap<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<T> IntoIterator for FrozenHeap<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter().rev())
+    }
+}
+
+/// An iterator over the elements of a [`FrozenHeap`], greatest first.
+#[derive(Clone)]
+pub struct Iter<'a, T>(Rev<slice::Iter<'a, T>>);
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {
+}
+impl<T> FusedIterator for Iter<'_, T> {
+}
+
+/// An owning iterator over the elements of a [`FrozenHeap`], greatest first.
+pub struct IntoIter<T>(Rev<vec::IntoIter<T>>);
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+impl<T> ExactSizeIterator for IntoIter<T> {
+}
+impl<T> FusedIterator for IntoIter<T> {
+}
+
+/// Freeze a [`BinaryHeap`] by sorting it.
+///
+/// For `T` which freezes to itself, this sorts in place without reallocating.
+impl<T: Freezable + Ord> Freezable for BinaryHeap<T> {
+    type Frozen = FrozenHeap<Frozen<T>>;
+
+    fn freeze(self) -> Frozen<Self> {
+        Frozen::new(FrozenHeap(freeze_vec(self.into_sorted_vec())))
+    }
+}
+/// Thawing reuses the sorted order: elements in descending order already form
+/// a valid heap, so rebuilding it takes a single O(n) pass with no swaps.
+impl<T: Freezable + Ord, U: Unfreezable<T> + Ord> Unfreezable<BinaryHeap<T>>
+    for BinaryHeap<U>
+{
+    fn thaw(FrozenHeap(sorted): <BinaryHeap<T> as Freezable>::Frozen) -> Self {
+        let mut vec: Vec<U> = thaw_vec(sorted);
+        vec.reverse();
+        BinaryHeap::from(vec)
+    }
+}
+impl<T: Freezable + Ord, U: CloneUnfreezable<T> + Ord> CloneUnfreezable<BinaryHeap<T>>
+    for BinaryHeap<U>
+{
+    fn thaw_cloned(FrozenHeap(sorted): &<BinaryHeap<T> as Freezable>::Frozen) -> Self {
+        BinaryHeap::from(
+            sorted
+                .iter()
+                .rev()
+                .map(Frozen::to_thawed)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+/// Thaws into the elements in ascending order.
+impl<T: Freezable + Ord, U: Unfreezable<T>> Unfreezable<BinaryHeap<T>> for Vec<U> {
+    fn thaw(FrozenHeap(sorted): <BinaryHeap<T> as Freezable>::Frozen) -> Self {
+        thaw_vec(sorted)
+    }
+}
+/// Thaws into the elements in ascending order.
+impl<T: Freezable + Ord, U: CloneUnfreezable<T>> CloneUnfreezable<BinaryHeap<T>>
+    for Vec<U>
+{
+    fn thaw_cloned(FrozenHeap(sorted): &<BinaryHeap<T> as Freezable>::Frozen) -> Self {
+        sorted.iter().map(Frozen::to_thawed).collect()
+    }
+}
@@ -0,0 +1,70 @@
+mod bit_set;
+mod builder;
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) mod hash_map;
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) mod hash_set;
+#[cfg(feature = "hashbrown")]
+pub mod hashbrown;
+mod heap;
+mod ordered;
+#[cfg(target_has_atomic = "ptr")]
+mod persistent;
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) mod small;
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+mod sort_cache;
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+use core::hash::{Hash, Hasher};
+
+pub use bit_set::{BitSet, BitSetElement, FrozenBitSet};
+pub use builder::FrozenVecBuilder;
+pub use heap::FrozenHeap;
+#[cfg(feature = "std")]
+pub(crate) use ordered::{freeze_vec, thaw_vec};
+#[cfg(target_has_atomic = "ptr")]
+pub use persistent::{Persistent, PersistentVec};
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) use sort_cache::SortCache;
+
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+use crate::fnv::Fnv;
+#[cfg(feature = "std")]
+pub use crate::frozen_std::collections::{
+    Bag,
+    DuplicateKey,
+    DuplicatePolicy,
+    FrozenBag,
+    FrozenMap,
+    FrozenMapBuilder,
+    FrozenMultiMap,
+    FrozenSet,
+    FrozenSetBuilder,
+    MultiMap,
+    PersistentMap,
+};
+#[cfg(all(feature = "std", feature = "hashbrown"))]
+pub use crate::frozen_std::collections::{
+    FrozenIndexMap,
+    FrozenIndexMapBuilder,
+    FrozenIndexSet,
+    FrozenIndexSetBuilder,
+    IndexedMap,
+    IndexedSet,
+};
+
+/// Hash the items of an unordered collection, such that the result does not
+/// depend on iteration order.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn hash_unordered<H: Hasher>(
+    items: impl Iterator<Item = impl Hash>,
+    state: &mut H,
+) {
+    let mut hash = 0;
+    for item in items {
+        let mut hasher = Fnv::default();
+        item.hash(&mut hasher);
+        hash ^= hasher.finish();
+    }
+    state.write_u64(hash);
+}
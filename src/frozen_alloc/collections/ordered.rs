@@ -0,0 +1,172 @@
+use alloc::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+use alloc::vec::Vec;
+use core::mem::ManuallyDrop;
+
+#[cfg(feature = "verify")]
+use crate::verify;
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
+
+/// Freeze every element of a [`Vec`].
+///
+/// If `T` freezes to itself, the allocation is reused as-is.
+pub(crate) fn freeze_vec<T: Freezable>(vec: Vec<T>) -> Vec<Frozen<T>> {
+    if T::SELF_FROZEN.is_some() {
+        // Each element becomes a `Frozen` without going through `Frozen::new`.
+        #[cfg(feature = "verify")]
+        vec.iter().for_each(verify::forget);
+        let mut vec = ManuallyDrop::new(vec);
+        // SAFETY: `T::SELF_FROZEN` can only be `Some` when `T::Frozen` is `T`,
+        // and `Frozen<T>` is a transparent wrapper around `T::Frozen`.
+        unsafe {
+            Vec::from_raw_parts(vec.as_mut_ptr().cast(), vec.len(), vec.capacity())
+        }
+    } else {
+        vec.into_iter().map(Freezable::freeze).collect()
+    }
+}
+
+/// Thaw every element of a frozen [`Vec`].
+///
+/// If `U` is `T` and `T` freezes to itself, the allocation is reused as-is.
+pub(crate) fn thaw_vec<T: Freezable, U: Unfreezable<T>>(vec: Vec<Frozen<T>>) -> Vec<U> {
+    if U::SELF_FROZEN.is_some() {
+        // Each element is thawed without going through `Frozen::thaw`.
+        #[cfg(feature = "verify")]
+        for frozen in &vec {
+            frozen.verify();
+            verify::forget(&frozen.0);
+        }
+        let mut vec = ManuallyDrop::new(vec);
+        // SAFETY: `U::SELF_FROZEN` can only be `Some` when `U` is `T` and
+        // `T::Frozen` is `T`, and `Frozen<T>` is a transparent wrapper around
+        // `T::Frozen`.
+        unsafe {
+            Vec::from_raw_parts(vec.as_mut_ptr().cast(), vec.len(), vec.capacity())
+        }
+    } else {
+        vec.into_iter().map(Frozen::thaw).collect()
+    }
+}
+
+macro_rules! freezable_impl {
+    ($($params:ident),* => $impl_type:ty, $frozen_type:ty) => {
+        impl<$($params: Freezable),*> Freezable for $impl_type {
+            type Frozen = Vec<Frozen<$frozen_type>>;
+
+            fn freeze(self) -> Frozen<Self> {
+                Frozen::new(self.into_iter().map(Freezable::freeze).collect())
+            }
+        }
+    };
+}
+macro_rules! unfreezable_impl {
+    ($unfreeze_type:ty, $($params:ident),* $(| $($bound_ty:ty : $bounds:tt),*)?) => {
+        #[allow(unused_parens)]
+        impl<
+            U: Unfreezable<($($params),*)>,
+            $($params: Freezable),*
+        > Unfreezable<$unfreeze_type> for Vec<U> {
+            fn thaw(wrapped: <$unfreeze_type as Freezable>::Frozen) -> Self {
+                wrapped.into_iter().map(Frozen::thaw).collect()
+            }
+        }
+        #[allow(unused_parens)]
+        impl<
+            U: CloneUnfreezable<($($params),*)>,
+            $($params: Freezable),*
+        > CloneUnfreezable<$unfreeze_type> for Vec<U> {
+            fn thaw_cloned(wrapped: &<$unfreeze_type as Freezable>::Frozen) -> Self {
+                wrapped.iter().map(Frozen::to_thawed).collect()
+            }
+        }
+        #[allow(unused_parens)]
+        impl<U: Freezable, $($params),*> Unfreezable<Vec<U>> for $unfreeze_type where
+            ($($params),*): Unfreezable<U>,
+            $($($bound_ty : $bounds),*)?
+        {
+            fn thaw(wrapped: <Vec<U> as Freezable>::Frozen) -> Self {
+                wrapped.into_iter().map(Frozen::thaw).collect()
+            }
+        }
+        #[allow(unused_parens)]
+        impl<U: Freezable, $($params),*> CloneUnfreezable<Vec<U>> for $unfreeze_type where
+            ($($params),*): CloneUnfreezable<U>,
+            $($($bound_ty : $bounds),*)?
+        {
+            fn thaw_cloned(wrapped: &<Vec<U> as Freezable>::Frozen) -> Self {
+                wrapped.iter().map(Frozen::to_thawed).collect()
+            }
+        }
+    };
+}
+
+/// Freeze a [`Vec`].
+///
+/// For `T` which freezes to itself, this is O(1).
+impl<T: Freezable> Freezable for Vec<T> {
+    type Frozen = Vec<Frozen<T>>;
+
+    fn freeze(self) -> Frozen<Self> {
+        Frozen::new(freeze_vec(self))
+    }
+}
+impl<U: Unfreezable<T>, T: Freezable> Unfreezable<Vec<T>> for Vec<U> {
+    fn thaw(wrapped: <Vec<T> as Freezable>::Frozen) -> Self {
+        thaw_vec(wrapped)
+    }
+}
+impl<U: CloneUnfreezable<T>, T: Freezable> CloneUnfreezable<Vec<T>> for Vec<U> {
+    fn thaw_cloned(wrapped: &<Vec<T> as Freezable>::Frozen) -> Self {
+        wrapped.iter().map(Frozen::to_thawed).collect()
+    }
+}
+
+/// Freeze a [`VecDeque`].
+///
+/// For `T` which freezes to itself, this does not reallocate, but may need to
+/// move the elements to the start of the buffer.
+impl<T: Freezable> Freezable for VecDeque<T> {
+    type Frozen = Vec<Frozen<T>>;
+
+    fn freeze(self) -> Frozen<Self> {
+        Frozen::new(freeze_vec(Vec::from(self)))
+    }
+}
+impl<U: Unfreezable<T>, T: Freezable> Unfreezable<VecDeque<T>> for Vec<U> {
+    fn thaw(wrapped: <VecDeque<T> as Freezable>::Frozen) -> Self {
+        thaw_vec(wrapped)
+    }
+}
+impl<U: Unfreezable<T>, T: Freezable> Unfreezable<Vec<T>> for VecDeque<U> {
+    fn thaw(wrapped: <Vec<T> as Freezable>::Frozen) -> Self {
+        VecDeque::from(thaw_vec(wrapped))
+    }
+}
+impl<U: Unfreezable<T>, T: Freezable> Unfreezable<VecDeque<T>> for VecDeque<U> {
+    fn thaw(wrapped: <VecDeque<T> as Freezable>::Frozen) -> Self {
+        VecDeque::from(thaw_vec(wrapped))
+    }
+}
+impl<U: CloneUnfreezable<T>, T: Freezable> CloneUnfreezable<VecDeque<T>> for Vec<U> {
+    fn thaw_cloned(wrapped: &<VecDeque<T> as Freezable>::Frozen) -> Self {
+        wrapped.iter().map(Frozen::to_thawed).collect()
+    }
+}
+impl<U: CloneUnfreezable<T>, T: Freezable> CloneUnfreezable<Vec<T>> for VecDeque<U> {
+    fn thaw_cloned(wrapped: &<Vec<T> as Freezable>::Frozen) -> Self {
+        wrapped.iter().map(Frozen::to_thawed).collect()
+    }
+}
+impl<U: CloneUnfreezable<T>, T: Freezable> CloneUnfreezable<VecDeque<T>>
+    for VecDeque<U>
+{
+    fn thaw_cloned(wrapped: &<VecDeque<T> as Freezable>::Frozen) -> Self {
+        wrapped.iter().map(Frozen::to_thawed).collect()
+    }
+}
+freezable_impl!(K, V => BTreeMap<K, V>, (K, V));
+unfreezable_impl!(BTreeMap<K, V>, K, V | K: Ord);
+freezable_impl!(T => BTreeSet<T>, T);
+unfreezable_impl!(BTreeSet<T>, T | T: Ord);
+freezable_impl!(T => LinkedList<T>, T);
+unfreezable_impl!(LinkedList<T>, T);
@@ -0,0 +1,15 @@
+mod vec;
+pub use vec::PersistentVec;
+
+/// A collection which freezes into a persistent form.
+///
+/// Persistent frozen collections can be "changed" without thawing: every
+/// update returns a new frozen value, which shares all untouched nodes with
+/// the original.
+///
+/// - `Persistent<HashMap<K, V, S>>` freezes into a
+///   [`PersistentMap`](crate::collections::PersistentMap), with the `std`
+///   feature
+/// - `Persistent<Vec<T>>` freezes into a [`PersistentVec`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Persistent<T>(pub T);
@@ -0,0 +1,570 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Formatter};
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::ops::Index;
+use core::slice;
+
+use super::Persistent;
+use crate::heap_size::{HeapSize, SharedAllocations};
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
+
+/// The number of index bits consumed by each level of the trie.
+const BITS: u32 = 5;
+const MASK: usize = (1 << BITS) - 1;
+
+/// A node of the trie. Every leaf is at depth `shift / BITS`, and every node
+/// but the last at each depth is full.
+enum Node<T> {
+    Branch(Vec<Arc<Node<T>>>),
+    Leaf(Vec<Arc<T>>),
+}
+impl<T> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Branch(children) => Node::Branch(children.clone()),
+            Node::Leaf(items) => Node::Leaf(items.clone()),
+        }
+    }
+}
+impl<T> Node<T> {
+    /// An empty node which would sit at `shift`.
+    fn empty(shift: u32) -> Self {
+        if shift == 0 {
+            Node::Leaf(Vec::new())
+        } else {
+            Node::Branch(Vec::new())
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Node::Branch(children) => children.is_empty(),
+            Node::Leaf(items) => items.is_empty(),
+        }
+    }
+
+    /// The leaf containing `index`, where this node sits at `shift`.
+    fn leaf(&self, mut shift: u32, index: usize) -> &[Arc<T>] {
+        let mut node = self;
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(index >> shift) & MASK];
+                    shift -= BITS;
+                }
+                Node::Leaf(items) => return items,
+            }
+        }
+    }
+
+    fn push(&mut self, shift: u32, index: usize, item: Arc<T>) {
+        match self {
+            Node::Branch(children) => {
+                let slot = (index >> shift) & MASK;
+                if slot == children.len() {
+                    children.push(Arc::new(Node::empty(shift - BITS)));
+                }
+                Arc::make_mut(&mut children[slot]).push(shift - BITS, index, item);
+            }
+            Node::Leaf(items) => items.push(item),
+        }
+    }
+
+    fn set(&mut self, shift: u32, index: usize, item: Arc<T>) {
+        match self {
+            Node::Branch(children) => {
+                let slot = (index >> shift) & MASK;
+                Arc::make_mut(&mut children[slot]).set(shift - BITS, index, item);
+            }
+            Node::Leaf(items) => items[index & MASK] = item,
+        }
+    }
+
+    /// Remove the last item, which is at `index`, pruning emptied nodes.
+    fn pop(&mut self, shift: u32, index: usize) {
+        match self {
+            Node::Branch(children) => {
+                let slot = (index >> shift) & MASK;
+                let child = Arc::make_mut(&mut children[slot]);
+                child.pop(shift - BITS, index);
+                if child.is_empty() {
+                    children.pop();
+                }
+            }
+            Node::Leaf(items) => {
+                items.pop();
+            }
+        }
+    }
+}
+
+/// A persistent vector, implemented as a chunked radix trie.
+///
+/// This is the frozen form of [`Persistent<Vec<T>>`](Persistent). Unlike a
+/// frozen [`Vec`], it can be updated in place: [`push`](Self::push),
+/// [`set`](Self::set) and [`pop`](Self::pop) return a new vector, which shares
+/// every untouched node with the original.
+///
+/// Hashing, equality and ordering are identical to a slice of the same items.
+pub struct PersistentVec<T> {
+    root: Arc<Node<T>>,
+    len: usize,
+    shift: u32,
+}
+impl<T> PersistentVec<T> {
+    /// Create an empty vector.
+    pub fn new() -> Self {
+        PersistentVec {
+            root: Arc::new(Node::empty(0)),
+            len: 0,
+            shift: 0,
+        }
+    }
+
+    /// The number of items in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the vector contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the item at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.root.leaf(self.shift, index)[index & MASK])
+    }
+
+    /// Get the first item.
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Get the last item.
+    pub fn last(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// An iterator over the items of the vector.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vec: self,
+            index: 0,
+            leaf: [].iter(),
+        }
+    }
+
+    /// Push an item in place, copying only the nodes shared with other
+    /// vectors.
+    pub(crate) fn push_mut(&mut self, item: T) {
+        if self.len == 1 << (self.shift + BITS) {
+            let root = self.root.clone();
+            self.root = Arc::new(Node::Branch(vec![root]));
+            self.shift += BITS;
+        }
+        Arc::make_mut(&mut self.root).push(self.shift, self.len, Arc::new(item));
+        self.len += 1;
+    }
+
+    /// Replace the item at `index` in place, copying only the nodes shared
+    /// with other vectors.
+    pub(crate) fn set_mut(&mut self, index: usize, item: T) {
+        assert!(
+            index < self.len,
+            "index out of bounds: the len is {} but the index is {index}",
+            self.len
+        );
+        Arc::make_mut(&mut self.root).set(self.shift, index, Arc::new(item));
+    }
+
+    /// Remove the last item in place, copying only the nodes shared with other
+    /// vectors. Returns whether there was an item to remove.
+    pub(crate) fn pop_mut(&mut self) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        self.len -= 1;
+        Arc::make_mut(&mut self.root).pop(self.shift, self.len);
+        while self.shift > 0 {
+            let Node::Branch(children) = &*self.root else {
+                break;
+            };
+            let [child] = children.as_slice() else {
+                break;
+            };
+            self.root = child.clone();
+            self.shift -= BITS;
+        }
+        true
+    }
+
+    /// Return a copy of this vector with `item` appended.
+    ///
+    /// Only the nodes on the path to the new item are copied; the rest are
+    /// shared.
+    #[must_use]
+    pub fn push(&self, item: T) -> Self {
+        let mut vec = self.clone();
+        vec.push_mut(item);
+        vec
+    }
+
+    /// Return a copy of this vector with the item at `index` replaced.
+    ///
+    /// Only the nodes on the path to `index` are copied; the rest are shared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn set(&self, index: usize, item: T) -> Self {
+        let mut vec = self.clone();
+        vec.set_mut(index, item);
+        vec
+    }
+
+    /// Return a copy of this vector without its last item, or `None` if it is
+    /// empty.
+    ///
+    /// Only the nodes on the path to the last item are copied; the rest are
+    /// shared.
+    #[must_use]
+    pub fn pop(&self) -> Option<Self> {
+        let mut vec = self.clone();
+        vec.pop_mut().then_some(vec)
+    }
+}
+impl<T> Clone for PersistentVec<T> {
+    fn clone(&self) -> Self {
+        PersistentVec {
+            root: self.root.clone(),
+            len: self.len,
+            shift: self.shift,
+        }
+    }
+}
+impl<T> Default for PersistentVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Debug> Debug for PersistentVec<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "PersistentVec(")?;
+        f.debug_list().entries(self.iter()).finish()?;
+        write!(f, ")")
+    }
+}
+impl<T: PartialEq> PartialEq for PersistentVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && (Arc::ptr_eq(&self.root, &other.root) || self.iter().eq(other.iter()))
+    }
+}
+impl<T: Eq> Eq for PersistentVec<T> {
+}
+impl<T: PartialOrd> PartialOrd for PersistentVec<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+impl<T: Ord> Ord for PersistentVec<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+impl<T: Hash> Hash for PersistentVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Matches `Hash for [T]`.
+        state.write_usize(self.len);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+impl<T> Index<usize> for PersistentVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            Some(item) => item,
+            None => {
+                panic!(
+                    "index out of bounds: the len is {} but the index is {index}",
+                    self.len
+                )
+            }
+        }
+    }
+}
+impl<T> FromIterator<T> for PersistentVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+impl<T> Extend<T> for PersistentVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_mut(item);
+        }
+    }
+}
+impl<'a, T> IntoIterator for &'a PersistentVec<T> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+/// Items still shared with other vectors are cloned.
+impl<T: Clone> IntoIterator for PersistentVec<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            stack: vec![vec![self.root].into_iter()],
+            leaf: Vec::new().into_iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+/// An iterator over the items of a [`PersistentVec`].
+pub struct Iter<'a, T> {
+    vec: &'a PersistentVec<T>,
+    index: usize,
+    leaf: slice::Iter<'a, Arc<T>>,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.leaf.len() == 0 {
+            if self.index >= self.vec.len {
+                return None;
+            }
+            self.leaf = self.vec.root.leaf(self.vec.shift, self.index).iter();
+        }
+        self.index += 1;
+        self.leaf.next().map(|item| &**item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.vec.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {
+}
+impl<T> FusedIterator for Iter<'_, T> {
+}
+
+/// An owning iterator over the items of a [`PersistentVec`].
+pub struct IntoIter<T> {
+    stack: Vec<vec::IntoIter<Arc<Node<T>>>>,
+    leaf: vec::IntoIter<Arc<T>>,
+    remaining: usize,
+}
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaf.next() {
+                self.remaining -= 1;
+                return Some(Arc::unwrap_or_clone(item));
+            }
+            match self.stack.last_mut()?.next() {
+                None => {
+                    self.stack.pop();
+                }
+                Some(node) => match Arc::unwrap_or_clone(node) {
+                    Node::Branch(children) => self.stack.push(children.into_iter()),
+                    Node::Leaf(items) => self.leaf = items.into_iter(),
+                },
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<T: Clone> ExactSizeIterator for IntoIter<T> {
+}
+impl<T: Clone> FusedIterator for IntoIter<T> {
+}
+
+/// Freeze a [`Vec`] into a [`PersistentVec`].
+impl<T: Freezable> Freezable for Persistent<Vec<T>> {
+    type Frozen = PersistentVec<Frozen<T>>;
+
+    fn freeze(self) -> Frozen<Self> {
+        Frozen::new(self.0.into_iter().map(Freezable::freeze).collect())
+    }
+}
+impl<T: Freezable> Frozen<Persistent<Vec<T>>> {
+    /// Return a copy of this vector with `item` appended, sharing all
+    /// untouched nodes with this vector.
+    #[must_use]
+    pub fn push(&self, item: T) -> Self {
+        Frozen::new(self.0.push(item.freeze()))
+    }
+
+    /// Return a copy of this vector with the item at `index` replaced, sharing
+    /// all untouched nodes with this vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn set(&self, index: usize, item: T) -> Self {
+        Frozen::new(self.0.set(index, item.freeze()))
+    }
+
+    /// Return a copy of this vector without its last item, sharing all
+    /// untouched nodes with this vector. Returns `None` if it is empty.
+    #[must_use]
+    pub fn pop(&self) -> Option<Self> {
+        self.0.pop().map(Frozen::new)
+    }
+}
+impl<U: Unfreezable<T>, T: Freezable> Unfreezable<Persistent<Vec<T>>> for Vec<U>
+where
+    T::Frozen: Clone,
+{
+    fn thaw(wrapped: <Persistent<Vec<T>> as Freezable>::Frozen) -> Self {
+        wrapped.into_iter().map(Frozen::thaw).collect()
+    }
+}
+impl<U: Unfreezable<T>, T: Freezable> Unfreezable<Persistent<Vec<T>>>
+    for Persistent<Vec<U>>
+where
+    T::Frozen: Clone,
+{
+    fn thaw(wrapped: <Persistent<Vec<T>> as Freezable>::Frozen) -> Self {
+        Persistent(wrapped.into_iter().map(Frozen::thaw).collect())
+    }
+}
+impl<U: CloneUnfreezable<T>, T: Freezable> CloneUnfreezable<Persistent<Vec<T>>>
+    for Vec<U>
+{
+    fn thaw_cloned(wrapped: &<Persistent<Vec<T>> as Freezable>::Frozen) -> Self {
+        wrapped.iter().map(Frozen::to_thawed).collect()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Node<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        match self {
+            Node::Branch(children) => children.heap_size_with(shared),
+            Node::Leaf(items) => items.heap_size_with(shared),
+        }
+    }
+}
+/// Nodes shared with other vectors are only counted once per measurement.
+impl<T: HeapSize> HeapSize for PersistentVec<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.root.heap_size_with(shared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The number of items a trie of the given shift can hold.
+    fn capacity(shift: u32) -> usize {
+        1 << (shift + BITS)
+    }
+
+    #[test]
+    fn push_grows_the_tree() {
+        let mut vec = PersistentVec::new();
+        for i in 0..capacity(0) {
+            vec.push_mut(i);
+        }
+        assert_eq!(vec.shift, 0);
+        vec.push_mut(capacity(0));
+        assert_eq!(vec.shift, BITS);
+        for i in vec.len..capacity(BITS) {
+            vec.push_mut(i);
+        }
+        assert_eq!(vec.shift, BITS);
+        vec.push_mut(capacity(BITS));
+        assert_eq!(vec.shift, 2 * BITS);
+        assert_eq!(vec.len(), capacity(BITS) + 1);
+        assert!(vec.iter().copied().eq(0..vec.len()));
+        for i in [0, 31, 32, 1023, 1024] {
+            assert_eq!(vec.get(i), Some(&i));
+        }
+        assert_eq!(vec.get(vec.len()), None);
+    }
+
+    #[test]
+    fn pop_shrinks_the_tree() {
+        let mut vec: PersistentVec<usize> = (0..=capacity(BITS)).collect();
+        assert_eq!(vec.shift, 2 * BITS);
+        assert!(vec.pop_mut());
+        assert_eq!(vec.shift, BITS);
+        while vec.len() > capacity(0) {
+            assert!(vec.pop_mut());
+        }
+        assert_eq!(vec.shift, 0);
+        assert!(vec.iter().copied().eq(0..capacity(0)));
+        while vec.pop_mut() {}
+        assert!(vec.is_empty());
+        assert_eq!(vec.shift, 0);
+        assert!(vec.root.is_empty());
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn push_and_pop_leave_the_original_unchanged() {
+        let vec: PersistentVec<usize> = (0..capacity(0)).collect();
+        let grown = vec.push(capacity(0));
+        let shrunk = grown.pop().unwrap();
+        let changed = grown.set(3, 100);
+        assert_eq!((vec.shift, grown.shift, shrunk.shift), (0, BITS, 0));
+        assert!(vec.iter().copied().eq(0..capacity(0)));
+        assert_eq!(shrunk, vec);
+        assert_eq!(grown.last(), Some(&capacity(0)));
+        assert_eq!((grown[3], changed[3]), (3, 100));
+        // Growing and shrinking share the original leaf instead of copying it.
+        let Node::Branch(children) = &*grown.root else {
+            panic!("a grown vector should have a branch at its root");
+        };
+        assert!(Arc::ptr_eq(&children[0], &vec.root));
+        assert!(Arc::ptr_eq(&shrunk.root, &vec.root));
+    }
+
+    #[test]
+    fn push_and_pop_across_heights() {
+        let mut vec = PersistentVec::new();
+        let mut model = Vec::new();
+        for round in 0..3 {
+            for i in 0..capacity(BITS) + 5 {
+                vec.push_mut(i + round);
+                model.push(i + round);
+            }
+            for _ in 0..capacity(BITS) - 20 {
+                assert!(vec.pop_mut());
+                model.pop();
+            }
+            assert!(vec.iter().eq(model.iter()));
+            assert_eq!(vec.len(), model.len());
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use core::hash::{BuildHasher, Hash};
+use core::iter::FusedIterator;
+use core::mem::{self, MaybeUninit};
+use core::ops::Range;
+use core::{ptr, slice};
+
+/// Frozen maps and sets with at most this many entries store them inline,
+/// and search them linearly instead of hashing the key.
+pub(crate) const SMALL_LEN: usize = 8;
+
+/// At most [`SMALL_LEN`] items stored inline, sorted by the hash of their key.
+///
+/// Sorting by hash gives the items an order which, like a hash table's, does
+/// not depend on the order they were inserted in.
+pub(crate) struct Small<T> {
+    len: usize,
+    items: [MaybeUninit<T>; SMALL_LEN],
+}
+impl<T> Small<T> {
+    fn new() -> Self {
+        Small {
+            len: 0,
+            items: [const { MaybeUninit::uninit() }; SMALL_LEN],
+        }
+    }
+
+    /// Collect `items`, sorted by the hash of their key under `hasher`. An
+    /// item whose key is already stored is passed to `merge` along with the
+    /// stored item, instead of being stored itself.
+    ///
+    /// # Panics
+    ///
+    /// If there are more than [`SMALL_LEN`] distinct keys.
+    pub(crate) fn collect<K: Hash + Eq + ?Sized>(
+        items: impl IntoIterator<Item = T>,
+        hasher: &impl BuildHasher,
+        key: impl Fn(&T) -> &K,
+        mut merge: impl FnMut(&mut T, T),
+    ) -> Self {
+        let mut small = Small::new();
+        let mut hashes = [0; SMALL_LEN];
+        for item in items {
+            if let Some(stored) = small
+                .as_mut_slice()
+                .iter_mut()
+                .find(|stored| key(stored) == key(&item))
+            {
+                merge(stored, item);
+                continue;
+            }
+            let hash = hasher.hash_one(key(&item));
+            let index = hashes[..small.len].partition_point(|&other| other <= hash);
+            small.insert(index, item);
+            hashes.copy_within(index..small.len - 1, index + 1);
+            hashes[index] = hash;
+        }
+        small
+    }
+
+    /// Insert `item` at `index`, moving the items after it along.
+    fn insert(&mut self, index: usize, item: T) {
+        assert!(self.len < SMALL_LEN, "too many items to store inline");
+        assert!(index <= self.len);
+        // SAFETY: there is room for one more item, so the items from `index`
+        // can move along one slot, leaving `index` free to write.
+        unsafe {
+            let slot = self.items.as_mut_ptr().add(index);
+            ptr::copy(slot, slot.add(1), self.len - index);
+            slot.write(MaybeUninit::new(item));
+        }
+        self.len += 1;
+    }
+
+    pub(crate) fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` items are initialized.
+        unsafe { slice::from_raw_parts(self.items.as_ptr().cast(), self.len) }
+    }
+
+    /// Call `f` with references to the items, sorted by `key`, without
+    /// allocating.
+    pub(crate) fn with_sorted<'a, K: Ord + ?Sized, R>(
+        &'a self,
+        key: impl Fn(&T) -> &K,
+        f: impl FnOnce(&[&'a T]) -> R,
+    ) -> R {
+        let items = self.as_slice();
+        let Some(first) = items.first() else {
+            return f(&[]);
+        };
+        let mut sorted = [first; SMALL_LEN];
+        for (slot, item) in sorted.iter_mut().zip(items) {
+            *slot = item;
+        }
+        let sorted = &mut sorted[..items.len()];
+        sorted.sort_unstable_by(|a, b| key(a).cmp(key(b)));
+        f(sorted)
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` items are initialized.
+        unsafe { slice::from_raw_parts_mut(self.items.as_mut_ptr().cast(), self.len) }
+    }
+}
+impl<T: Clone> Clone for Small<T> {
+    fn clone(&self) -> Self {
+        let mut small = Small::new();
+        for item in self.as_slice() {
+            small.insert(small.len, item.clone());
+        }
+        small
+    }
+}
+impl<T> Drop for Small<T> {
+    fn drop(&mut self) {
+        // SAFETY: the first `len` items are initialized, and are not used
+        // again.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+impl<T> IntoIterator for Small<T> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(mut self) -> IntoIter<T> {
+        let live = 0..mem::take(&mut self.len);
+        let items = mem::replace(
+            &mut self.items,
+            [const { MaybeUninit::uninit() }; SMALL_LEN],
+        );
+        IntoIter { items, live }
+    }
+}
+
+/// An owning iterator over the items of a [`Small`].
+pub(crate) struct IntoIter<T> {
+    items: [MaybeUninit<T>; SMALL_LEN],
+    /// The items which are initialized and not yet returned.
+    live: Range<usize>,
+}
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let index = self.live.next()?;
+        // SAFETY: the item at `index` was live, and is no longer.
+        Some(unsafe { self.items[index].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.live.size_hint()
+    }
+}
+impl<T> ExactSizeIterator for IntoIter<T> {
+}
+impl<T> FusedIterator for IntoIter<T> {
+}
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for item in &mut self.items[self.live.clone()] {
+            // SAFETY: the live items are initialized, and are not used again.
+            unsafe { item.assume_init_drop() }
+        }
+    }
+}
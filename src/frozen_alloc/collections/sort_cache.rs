@@ -0,0 +1,66 @@
+use alloc::boxed::Box;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Pointers to the items of a frozen hash table, in sorted order, computed
+/// the first time the table is compared.
+///
+/// The pointers stay valid for as long as the table which owns the cache, as
+/// a frozen table is never modified and its items never move. A clone of the
+/// cache starts out empty, as the clone of its table has new items.
+pub(crate) struct SortCache<P>(AtomicPtr<Box<[P]>>);
+impl<P: Copy> SortCache<P> {
+    pub(crate) const fn new() -> Self {
+        SortCache(AtomicPtr::new(ptr::null_mut()))
+    }
+
+    /// The sorted pointers, computing them with `sort` if this is the first
+    /// call.
+    pub(crate) fn get_or_init(&self, sort: impl FnOnce() -> Box<[P]>) -> &[P] {
+        let mut sorted = self.0.load(Ordering::Acquire);
+        if sorted.is_null() {
+            let new = Box::into_raw(Box::new(sort()));
+            match self.0.compare_exchange(
+                ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => sorted = new,
+                Err(other) => {
+                    // SAFETY: `new` was never published.
+                    drop(unsafe { Box::from_raw(new) });
+                    sorted = other;
+                }
+            }
+        }
+        // SAFETY: once published, the pointers are never changed or freed
+        // until the cache is dropped.
+        unsafe { &*sorted }
+    }
+}
+impl<P: Copy> Clone for SortCache<P> {
+    fn clone(&self) -> Self {
+        SortCache::new()
+    }
+}
+impl<P> Drop for SortCache<P> {
+    fn drop(&mut self) {
+        let sorted = *self.0.get_mut();
+        if !sorted.is_null() {
+            // SAFETY: `sorted` came from `Box::into_raw`, and the cache is
+            // gone.
+            drop(unsafe { Box::from_raw(sorted) });
+        }
+    }
+}
+// SAFETY: the pointers are only dereferenced by the table which owns the
+// cache, which is itself only `Send` and `Sync` if its items are.
+unsafe impl<P> Send for SortCache<P> {
+}
+unsafe impl<P> Sync for SortCache<P> {
+}
+// SAFETY: filling the cache cannot change anything observable: the table
+// compares the same way whether or not its sorted order is cached.
+unsafe impl<P> crate::DeepImmutable for SortCache<P> {
+}
@@ -0,0 +1,221 @@
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "hashbrown")]
+use core::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::{HashMap, HashSet};
+
+#[cfg(feature = "hashbrown")]
+use crate::collections::hashbrown::{FrozenMap, FrozenSet};
+use crate::collections::FrozenHeap;
+use crate::convert::FromElements;
+use crate::{DeepImmutable, Freezable, Frozen};
+
+macro_rules! impl_from_elements {
+    ($($params:ident $(: $bound:path)?),* => $impl_type:ty, $element:ty) => {
+        impl<$($params $(: $bound)?),*> FromElements for $impl_type {
+            type Element = $element;
+        }
+    };
+}
+impl_from_elements!(T => Vec<T>, T);
+impl_from_elements!(T => VecDeque<T>, T);
+impl_from_elements!(T => LinkedList<T>, T);
+impl_from_elements!(T: Ord => BinaryHeap<T>, T);
+impl_from_elements!(T: Ord => BTreeSet<T>, T);
+impl_from_elements!(K: Ord, V => BTreeMap<K, V>, (K, V));
+impl_from_elements!(T => Box<[T]>, T);
+impl_from_elements!(T => Rc<[T]>, T);
+#[cfg(target_has_atomic = "ptr")]
+impl_from_elements!(T => Arc<[T]>, T);
+impl_from_elements!(=> String, char);
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq, S: BuildHasher + Default> FromElements for HashSet<T, S> {
+    type Element = T;
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromElements for HashMap<K, V, S> {
+    type Element = (K, V);
+}
+
+/// Sort frozen map entries by key. Keys are unique, so the order is total.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn sort_entries<K: Ord, V>(entries: &mut [(K, V)]) {
+    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+}
+
+/// Sort a list of frozen entries by key, keeping the last value for each key,
+/// like [`BTreeMap::from_iter`].
+fn sort_dedup_entries<K: Ord, V>(entries: &mut Vec<(K, V)>) {
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries.reverse();
+    entries.dedup_by(|(a, _), (b, _)| a == b);
+    entries.reverse();
+}
+
+macro_rules! impl_same_form {
+    ($($from:ident => $to:ident),*) => {
+        $(
+            /// The frozen forms are the same, so this is free.
+            impl<T: Freezable> From<Frozen<$from<T>>> for Frozen<$to<T>> {
+                fn from(frozen: Frozen<$from<T>>) -> Self {
+                    frozen.rewrap()
+                }
+            }
+        )*
+    };
+}
+impl_same_form!(
+    Vec => VecDeque,
+    Vec => LinkedList,
+    VecDeque => Vec,
+    VecDeque => LinkedList,
+    LinkedList => Vec,
+    LinkedList => VecDeque,
+    BTreeSet => Vec,
+    BTreeSet => VecDeque
+);
+/// The frozen forms are the same, so this is free. The entries are in key
+/// order.
+impl<K: Freezable, V: Freezable> From<Frozen<BTreeMap<K, V>>> for Frozen<Vec<(K, V)>> {
+    fn from(frozen: Frozen<BTreeMap<K, V>>) -> Self {
+        frozen.rewrap()
+    }
+}
+
+macro_rules! impl_array_conversions {
+    ($($seq:ident),*) => {
+        $(
+            impl<T: Freezable, const N: usize> From<Frozen<[T; N]>> for Frozen<$seq<T>> {
+                fn from(frozen: Frozen<[T; N]>) -> Self {
+                    frozen.verify();
+                    Frozen::new(frozen.into_inner().into_iter().collect())
+                }
+            }
+            /// Fails, returning the original value, unless there are exactly
+            /// `N` elements.
+            impl<T: Freezable, const N: usize> TryFrom<Frozen<$seq<T>>> for Frozen<[T; N]> {
+                type Error = Frozen<$seq<T>>;
+
+                fn try_from(frozen: Frozen<$seq<T>>) -> Result<Self, Self::Error> {
+                    if frozen.len() != N {
+                        return Err(frozen);
+                    }
+                    frozen.verify();
+                    let mut elements = frozen.into_inner().into_iter();
+                    Ok(Frozen::new(core::array::from_fn(|_| elements.next().unwrap())))
+                }
+            }
+        )*
+    };
+}
+impl_array_conversions!(Vec, VecDeque, LinkedList);
+
+/// Keeps the elements in ascending order.
+impl<T: Freezable + Ord> From<Frozen<BinaryHeap<T>>> for Frozen<Vec<T>> {
+    fn from(frozen: Frozen<BinaryHeap<T>>) -> Self {
+        frozen.verify();
+        Frozen::new(frozen.into_inner().into_sorted_vec())
+    }
+}
+impl<T: Freezable + Ord> From<Frozen<Vec<T>>> for Frozen<BinaryHeap<T>>
+where
+    T::Frozen: Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<Vec<T>>) -> Self {
+        frozen.verify();
+        let mut sorted = frozen.into_inner();
+        sorted.sort_unstable();
+        Frozen::new(FrozenHeap(sorted))
+    }
+}
+/// Keeps one of each run of equal elements.
+impl<T: Freezable> From<Frozen<Vec<T>>> for Frozen<BTreeSet<T>>
+where
+    T::Frozen: Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<Vec<T>>) -> Self {
+        frozen.verify();
+        let mut sorted = frozen.into_inner();
+        sorted.sort_unstable();
+        sorted.dedup();
+        Frozen::new(sorted)
+    }
+}
+/// Keeps the last value for each key, like [`BTreeMap::from_iter`].
+impl<K: Freezable, V: Freezable> From<Frozen<Vec<(K, V)>>> for Frozen<BTreeMap<K, V>>
+where
+    K::Frozen: Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<Vec<(K, V)>>) -> Self {
+        frozen.verify();
+        let mut entries: Vec<_> = frozen
+            .into_inner()
+            .into_iter()
+            .map(Frozen::into_inner)
+            .collect();
+        sort_dedup_entries(&mut entries);
+        Frozen::new(entries.into_iter().map(Frozen::new).collect())
+    }
+}
+
+#[cfg(feature = "hashbrown")]
+impl<T: Freezable, S: BuildHasher + Clone> From<Frozen<HashSet<T, S>>>
+    for Frozen<BTreeSet<T>>
+where
+    T::Frozen: Hash + Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<HashSet<T, S>>) -> Self {
+        frozen.verify();
+        let mut sorted: Vec<_> = frozen.into_inner().into_iter().collect();
+        sorted.sort_unstable();
+        Frozen::new(sorted)
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Freezable, S: BuildHasher + Clone + Default> From<Frozen<BTreeSet<T>>>
+    for Frozen<HashSet<T, S>>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn from(frozen: Frozen<BTreeSet<T>>) -> Self {
+        frozen.verify();
+        Frozen::new_verified(FrozenSet::from_elements(
+            frozen.into_inner().into_iter(),
+            S::default(),
+        ))
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone> From<Frozen<HashMap<K, V, S>>>
+    for Frozen<BTreeMap<K, V>>
+where
+    K::Frozen: Hash + Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<HashMap<K, V, S>>) -> Self {
+        frozen.verify();
+        let mut entries: Vec<_> = frozen.into_inner().into_iter().collect();
+        sort_entries(&mut entries);
+        Frozen::new(entries.into_iter().map(Frozen::new).collect())
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone + Default>
+    From<Frozen<BTreeMap<K, V>>> for Frozen<HashMap<K, V, S>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn from(frozen: Frozen<BTreeMap<K, V>>) -> Self {
+        frozen.verify();
+        Frozen::new(FrozenMap::from_entries(
+            frozen.into_inner().into_iter().map(Frozen::into_inner),
+            S::default(),
+        ))
+    }
+}
@@ -0,0 +1,305 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "hashbrown")]
+use crate::collections::hashbrown::{FrozenMap, FrozenSet};
+use crate::diff::{impl_replace_diff, FrozenDiff};
+
+impl_replace_diff!(String);
+
+/// The changes between two maps.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MapPatch<K, V: FrozenDiff> {
+    /// Entries only present in the new map.
+    pub added: Vec<(K, V)>,
+    /// Keys only present in the old map.
+    pub removed: Vec<K>,
+    /// Keys present in both maps, with how their values changed.
+    pub changed: Vec<(K, V::Patch)>,
+}
+
+/// Diff the entries of two maps, given a lookup into the new map.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub(crate) fn diff_entries<'a, K: Clone + 'a, V: FrozenDiff + Clone + 'a>(
+    old: impl Iterator<Item = (&'a K, &'a V)>,
+    new: impl Iterator<Item = (&'a K, &'a V)>,
+    get_old: impl Fn(&K) -> Option<&'a V>,
+    get_new: impl Fn(&K) -> Option<&'a V>,
+) -> Option<MapPatch<K, V>> {
+    let mut patch = MapPatch {
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for (key, value) in old {
+        match get_new(key) {
+            None => patch.removed.push(key.clone()),
+            Some(new) => {
+                if let Some(change) = value.diff(new) {
+                    patch.changed.push((key.clone(), change));
+                }
+            }
+        }
+    }
+    for (key, value) in new {
+        if get_old(key).is_none() {
+            patch.added.push((key.clone(), value.clone()));
+        }
+    }
+    let unchanged =
+        patch.added.is_empty() && patch.removed.is_empty() && patch.changed.is_empty();
+    (!unchanged).then_some(patch)
+}
+
+/// The changes between two sets.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SetPatch<T> {
+    /// Elements only present in the new set.
+    pub added: Vec<T>,
+    /// Elements only present in the old set.
+    pub removed: Vec<T>,
+}
+/// Implement [`FrozenDiff`] for a frozen hash map and hash set, given the names
+/// of their tuple structs.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+macro_rules! impl_hash_diff {
+    ($map:ident, $set:ident) => {
+        impl<K, V, S> $crate::diff::FrozenDiff for $map<K, V, S>
+        where
+            K: core::hash::Hash + Eq + Clone,
+            V: $crate::diff::FrozenDiff + Clone,
+            S: core::hash::BuildHasher + Clone,
+        {
+            type Patch = $crate::diff::MapPatch<K, V>;
+
+            fn diff(&self, new: &Self) -> Option<Self::Patch> {
+                $crate::frozen_alloc::diff::diff_entries(
+                    self.iter(),
+                    new.iter(),
+                    |key| self.get(key),
+                    |key| new.get(key),
+                )
+            }
+
+            fn patch(&self, patch: &Self::Patch) -> Self {
+                let mut map = self.to_map();
+                for key in &patch.removed {
+                    map.remove(key);
+                }
+                for (key, change) in &patch.changed {
+                    if let Some(value) = map.get_mut(key) {
+                        *value = value.patch(change);
+                    }
+                }
+                map.extend(patch.added.iter().cloned());
+                $map::from_map(map)
+            }
+        }
+
+        impl<T, S> $crate::diff::FrozenDiff for $set<T, S>
+        where
+            T: core::hash::Hash + Eq + Clone,
+            S: core::hash::BuildHasher + Clone,
+        {
+            type Patch = $crate::diff::SetPatch<T>;
+
+            fn diff(&self, new: &Self) -> Option<Self::Patch> {
+                let patch = $crate::diff::SetPatch {
+                    added: new.difference(self).cloned().collect(),
+                    removed: self.difference(new).cloned().collect(),
+                };
+                (!patch.added.is_empty() || !patch.removed.is_empty()).then_some(patch)
+            }
+
+            fn patch(&self, patch: &Self::Patch) -> Self {
+                let mut set = self.to_set();
+                for value in &patch.removed {
+                    set.remove(value);
+                }
+                set.extend(patch.added.iter().cloned());
+                $set::from_set(set)
+            }
+        }
+    };
+}
+#[cfg(feature = "std")]
+pub(crate) use impl_hash_diff;
+
+#[cfg(feature = "hashbrown")]
+impl_hash_diff!(FrozenMap, FrozenSet);
+
+/// One step of a [`SeqPatch`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Edit<T: FrozenDiff> {
+    /// Keep the next `n` items.
+    Keep(usize),
+    /// Drop the next `n` items.
+    Remove(usize),
+    /// Insert new items.
+    Insert(Vec<T>),
+    /// Replace the next item with the result of patching it.
+    Change(T::Patch),
+}
+
+/// The changes between two sequences, as an edit script which walks the old
+/// sequence from start to end.
+pub type SeqPatch<T> = Vec<Edit<T>>;
+
+/// Push an edit, merging it into the previous one where possible.
+fn push_edit<T: FrozenDiff>(edits: &mut Vec<Edit<T>>, edit: Edit<T>) {
+    match (edits.last_mut(), edit) {
+        (Some(Edit::Keep(n)), Edit::Keep(m))
+        | (Some(Edit::Remove(n)), Edit::Remove(m)) => {
+            *n += m;
+        }
+        (Some(Edit::Insert(items)), Edit::Insert(mut more)) => items.append(&mut more),
+        (_, edit) => edits.push(edit),
+    }
+}
+
+/// Whether `old` and `new` have no changes between them. This differs from
+/// equality for floats, which are compared by their bits.
+fn same<T: FrozenDiff>(old: &T, new: &T) -> bool {
+    old.diff(new).is_none()
+}
+
+/// Find a shortest edit script from `old` to `new` with Myers' algorithm,
+/// returning the `(old, new)` index of every item kept, in order.
+fn common_items<T: FrozenDiff>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0; 2 * offset as usize + 1];
+    // The part of `v` read by each step, for backtracking.
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let index = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && same(&old[x as usize], &new[y as usize]) {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut common = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        // `v` starts at diagonal `-d - 1`.
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            common.push((x as usize, y as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    common.reverse();
+    common
+}
+
+/// Emit the edits turning `old` into `new`, where no items are kept between
+/// them. Items removed and inserted at the same point are paired up and
+/// diffed against each other.
+fn push_replacement<T: FrozenDiff + Clone>(
+    edits: &mut Vec<Edit<T>>,
+    old: &[T],
+    new: &[T],
+) {
+    let paired = old.len().min(new.len());
+    for (old, new) in old.iter().zip(new).take(paired) {
+        match old.diff(new) {
+            Some(change) => push_edit(edits, Edit::Change(change)),
+            None => push_edit(edits, Edit::Keep(1)),
+        }
+    }
+    if old.len() > paired {
+        push_edit(edits, Edit::Remove(old.len() - paired));
+    }
+    if new.len() > paired {
+        push_edit(edits, Edit::Insert(new[paired..].to_vec()));
+    }
+}
+
+/// Sequences are diffed into a minimal edit script, with adjacent removals
+/// and insertions paired up into changes. Items are matched up when diffing
+/// them finds no changes, rather than when they are equal.
+impl<T: FrozenDiff + Clone> FrozenDiff for Vec<T> {
+    type Patch = SeqPatch<T>;
+
+    fn diff(&self, new: &Self) -> Option<Self::Patch> {
+        let prefix = self
+            .iter()
+            .zip(new)
+            .take_while(|(old, new)| same(*old, *new))
+            .count();
+        if prefix == self.len() && prefix == new.len() {
+            return None;
+        }
+        let suffix = self[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(old, new)| same(*old, *new))
+            .count();
+        let old_middle = &self[prefix..self.len() - suffix];
+        let new_middle = &new[prefix..new.len() - suffix];
+
+        let mut edits = Vec::new();
+        push_edit(&mut edits, Edit::Keep(prefix));
+        let (mut x, mut y) = (0, 0);
+        for (common_x, common_y) in common_items(old_middle, new_middle) {
+            push_replacement(
+                &mut edits,
+                &old_middle[x..common_x],
+                &new_middle[y..common_y],
+            );
+            push_edit(&mut edits, Edit::Keep(1));
+            (x, y) = (common_x + 1, common_y + 1);
+        }
+        push_replacement(&mut edits, &old_middle[x..], &new_middle[y..]);
+        push_edit(&mut edits, Edit::Keep(suffix));
+        edits.retain(|edit| !matches!(edit, Edit::Keep(0)));
+        Some(edits)
+    }
+
+    fn patch(&self, patch: &Self::Patch) -> Self {
+        let mut items = Vec::with_capacity(self.len());
+        let mut old = self.iter();
+        for edit in patch {
+            match edit {
+                Edit::Keep(n) => items.extend(old.by_ref().take(*n).cloned()),
+                Edit::Remove(n) => {
+                    old.by_ref().take(*n).for_each(drop);
+                }
+                Edit::Insert(new) => items.extend(new.iter().cloned()),
+                Edit::Change(change) => {
+                    items.extend(old.next().map(|old| old.patch(change)))
+                }
+            }
+        }
+        items
+    }
+}
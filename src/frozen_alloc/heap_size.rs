@@ -0,0 +1,159 @@
+use alloc::boxed::Box;
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+#[cfg(feature = "hashbrown")]
+use core::hash::{BuildHasher, Hash};
+use core::mem;
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::{HashMap, HashSet, HashTable};
+
+#[cfg(target_has_atomic = "ptr")]
+use crate::collections::Persistent;
+use crate::collections::{FrozenBitSet, FrozenHeap};
+use crate::heap_size::{HeapSize, SharedAllocations};
+use crate::value::{FrozenArray, FrozenStr, FrozenValue, FrozenValueMap};
+
+/// The size of an `Rc` or `Arc` allocation holding `value`, including the
+/// reference counts.
+pub(crate) fn counted_size<T: ?Sized>(value: &T) -> usize {
+    Layout::new::<[usize; 2]>()
+        .extend(Layout::for_value(value))
+        .unwrap()
+        .0
+        .pad_to_align()
+        .size()
+}
+
+impl HeapSize for String {
+    fn heap_size_with(&self, _shared: &mut SharedAllocations) -> usize {
+        self.capacity()
+    }
+}
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.as_slice().heap_size_with(shared)
+    }
+}
+impl<T: HeapSize> HeapSize for VecDeque<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self
+                .iter()
+                .map(|item| item.heap_size_with(shared))
+                .sum::<usize>()
+    }
+}
+impl<T: HeapSize> HeapSize for BinaryHeap<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.as_slice().heap_size_with(shared)
+    }
+}
+impl<T: HeapSize> HeapSize for FrozenHeap<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+impl<T> HeapSize for FrozenBitSet<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.words.heap_size_with(shared)
+    }
+}
+impl<T: HeapSize + ?Sized> HeapSize for Box<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        mem::size_of_val::<T>(self) + (**self).heap_size_with(shared)
+    }
+}
+/// Counted the first time the allocation is reached.
+impl<T: HeapSize + ?Sized> HeapSize for Rc<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        if shared.insert(Rc::as_ptr(self)) {
+            counted_size::<T>(self) + (**self).heap_size_with(shared)
+        } else {
+            0
+        }
+    }
+}
+/// Counted the first time the allocation is reached.
+#[cfg(target_has_atomic = "ptr")]
+impl<T: HeapSize + ?Sized> HeapSize for Arc<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        if shared.insert(Arc::as_ptr(self)) {
+            counted_size::<T>(self) + (**self).heap_size_with(shared)
+        } else {
+            0
+        }
+    }
+}
+#[cfg(target_has_atomic = "ptr")]
+impl<T: HeapSize> HeapSize for Persistent<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+
+#[cfg(feature = "hashbrown")]
+impl<T: HeapSize> HeapSize for HashTable<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.allocation_size()
+            + self
+                .iter()
+                .map(|item| item.heap_size_with(shared))
+                .sum::<usize>()
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + HeapSize, V: HeapSize, S: BuildHasher> HeapSize
+    for HashMap<K, V, S>
+{
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.allocation_size()
+            + self
+                .iter()
+                .map(|(key, value)| {
+                    key.heap_size_with(shared) + value.heap_size_with(shared)
+                })
+                .sum::<usize>()
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + HeapSize, S: BuildHasher> HeapSize for HashSet<T, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.allocation_size()
+            + self
+                .iter()
+                .map(|item| item.heap_size_with(shared))
+                .sum::<usize>()
+    }
+}
+
+impl HeapSize for FrozenStr {
+    fn heap_size_with(&self, _shared: &mut SharedAllocations) -> usize {
+        self.len()
+    }
+}
+impl HeapSize for FrozenArray {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+impl HeapSize for FrozenValueMap {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+impl HeapSize for FrozenValue {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        match self {
+            FrozenValue::String(value) => value.heap_size_with(shared),
+            FrozenValue::Array(value) => value.heap_size_with(shared),
+            FrozenValue::Map(value) => value.heap_size_with(shared),
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! A history of frozen snapshots, with undo and redo.
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::diff::FrozenDiff;
+use crate::{Freezable, Frozen};
+
+/// How a version is stored.
+enum Delta<T: Freezable>
+where
+    T::Frozen: FrozenDiff,
+{
+    /// The whole value.
+    Snapshot(Frozen<T>),
+    /// The changes from the parent version.
+    Patch(<T::Frozen as FrozenDiff>::Patch),
+}
+
+struct Version<T: Freezable>
+where
+    T::Frozen: FrozenDiff,
+{
+    parent: Option<usize>,
+    /// The child which [`FrozenHistory::redo`] moves to.
+    redo: Option<usize>,
+    /// The number of patches between this version and the nearest snapshot.
+    patches: usize,
+    delta: Delta<T>,
+}
+
+/// A branching history of frozen versions of a `T`.
+///
+/// Each version is stored as a patch against its parent, so memory use grows
+/// with the size of the changes rather than the size of the value. A full
+/// snapshot is kept every few versions, to bound the cost of reconstructing
+/// old versions.
+///
+/// Versions are numbered from 0 (the initial value) in the order they were
+/// committed. Committing after an undo starts a new branch; the abandoned
+/// branch stays reachable through [`at`](Self::at) and
+/// [`checkout`](Self::checkout) until it is pruned.
+pub struct FrozenHistory<T: Freezable>
+where
+    T::Frozen: FrozenDiff,
+{
+    versions: Vec<Option<Version<T>>>,
+    current: usize,
+    value: Frozen<T>,
+    snapshot_interval: usize,
+}
+impl<T: Freezable> FrozenHistory<T>
+where
+    T::Frozen: FrozenDiff + Clone,
+{
+    /// Start a history at `initial`, which becomes version 0.
+    pub fn new(initial: Frozen<T>) -> Self {
+        FrozenHistory {
+            versions: vec![Some(Version {
+                parent: None,
+                redo: None,
+                patches: 0,
+                delta: Delta::Snapshot(initial.clone()),
+            })],
+            current: 0,
+            value: initial,
+            snapshot_interval: 32,
+        }
+    }
+
+    /// Set how many patches may be stacked on top of each snapshot. Defaults
+    /// to 32.
+    ///
+    /// Lower values make [`at`](Self::at), [`undo`](Self::undo) and
+    /// [`checkout`](Self::checkout) faster, at the cost of more memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    #[must_use]
+    pub fn with_snapshot_interval(mut self, interval: usize) -> Self {
+        assert!(interval > 0, "snapshot interval must be positive");
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// The current value.
+    pub fn current(&self) -> &Frozen<T> {
+        &self.value
+    }
+
+    /// The current version number.
+    pub fn version(&self) -> usize {
+        self.current
+    }
+
+    /// The number of versions which have not been pruned.
+    pub fn version_count(&self) -> usize {
+        self.versions.iter().flatten().count()
+    }
+
+    /// Whether the initial version is the only one which has not been
+    /// pruned.
+    pub fn has_only_initial(&self) -> bool {
+        self.version_count() == 1
+    }
+
+    /// Whether `version` exists and has not been pruned.
+    pub fn contains(&self, version: usize) -> bool {
+        matches!(self.versions.get(version), Some(Some(_)))
+    }
+
+    fn get(&self, version: usize) -> &Version<T> {
+        self.versions[version]
+            .as_ref()
+            .expect("version should not have been pruned")
+    }
+
+    fn get_mut(&mut self, version: usize) -> &mut Version<T> {
+        self.versions[version]
+            .as_mut()
+            .expect("version should not have been pruned")
+    }
+
+    /// Commit `value` as a child of the current version, and make it current.
+    ///
+    /// Returns the new version number. If [`FrozenDiff::diff`] finds no
+    /// changes from the current value, nothing is committed and the current
+    /// version number is returned. Floats are compared by their bits, so
+    /// committing a NaN over itself changes nothing, but committing `-0.0`
+    /// over `0.0` does.
+    pub fn commit(&mut self, value: Frozen<T>) -> usize {
+        let Some(patch) = self.value.diff(&value) else {
+            return self.current;
+        };
+        let patches = self.get(self.current).patches + 1;
+        let (patches, delta) = if patches >= self.snapshot_interval {
+            (0, Delta::Snapshot(value.clone()))
+        } else {
+            (patches, Delta::Patch(patch))
+        };
+        let version = self.versions.len();
+        self.versions.push(Some(Version {
+            parent: Some(self.current),
+            redo: None,
+            patches,
+            delta,
+        }));
+        self.get_mut(self.current).redo = Some(version);
+        self.current = version;
+        self.value = value;
+        version
+    }
+
+    /// Reconstruct the value at `version`, or `None` if it does not exist or
+    /// has been pruned.
+    pub fn at(&self, version: usize) -> Option<Frozen<T>> {
+        if !self.contains(version) {
+            return None;
+        }
+        if version == self.current {
+            return Some(self.value.clone());
+        }
+        let mut patches = Vec::new();
+        let mut next = version;
+        let base = loop {
+            let Version { parent, delta, .. } = self.get(next);
+            match delta {
+                Delta::Snapshot(value) => break value,
+                Delta::Patch(patch) => patches.push(patch),
+            }
+            next = parent.expect("the initial version should be a snapshot");
+        };
+        let Some((first, rest)) = patches.split_last() else {
+            return Some(base.clone());
+        };
+        let mut value = base.patch(first);
+        for patch in rest.iter().rev() {
+            value = value.patch(patch);
+        }
+        Some(value)
+    }
+
+    /// Make `version` current, returning its value, or `None` if it does not
+    /// exist or has been pruned.
+    ///
+    /// Redo will retrace the path from the previous version.
+    pub fn checkout(&mut self, version: usize) -> Option<&Frozen<T>> {
+        self.value = self.at(version)?;
+        self.current = version;
+        Some(&self.value)
+    }
+
+    /// Move to the parent of the current version, returning its value, or
+    /// `None` if the current version is the initial one.
+    pub fn undo(&mut self) -> Option<&Frozen<T>> {
+        let child = self.current;
+        let parent = self.get(child).parent?;
+        self.checkout(parent)?;
+        self.get_mut(parent).redo = Some(child);
+        Some(&self.value)
+    }
+
+    /// Move to the most recently committed or undone child of the current
+    /// version, returning its value, or `None` if there is none.
+    pub fn redo(&mut self) -> Option<&Frozen<T>> {
+        let child = self.get(self.current).redo?;
+        self.checkout(child)
+    }
+
+    /// Discard every version which cannot be reached from the current version
+    /// with [`undo`](Self::undo) and [`redo`](Self::redo).
+    ///
+    /// Version numbers of the remaining versions are unchanged.
+    pub fn prune_branches(&mut self) {
+        let mut keep = vec![false; self.versions.len()];
+        keep[self.current] = true;
+        let mut child = self.current;
+        while let Some(parent) = self.get(child).parent {
+            keep[parent] = true;
+            // The redo path from an ancestor may lead into a pruned branch.
+            self.get_mut(parent).redo = Some(child);
+            child = parent;
+        }
+        let mut next = self.get(self.current).redo;
+        while let Some(version) = next {
+            keep[version] = true;
+            next = self.get(version).redo;
+        }
+        for (version, keep) in self.versions.iter_mut().zip(keep) {
+            if !keep {
+                *version = None;
+            }
+        }
+    }
+}
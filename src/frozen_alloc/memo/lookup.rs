@@ -0,0 +1,257 @@
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::time::Duration;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::collections::hash_unordered;
+use crate::value::{FrozenStr, FrozenValue};
+use crate::{DeepImmutable, Freezable, FreezesToSelf, Frozen};
+
+/// A value which can be looked up among frozen keys without freezing it.
+///
+/// [`Memo`](super::Memo) caches are keyed by frozen arguments, but are
+/// searched with the arguments as they were passed, so a cache hit neither
+/// freezes nor allocates anything.
+///
+/// Hash-based collections can only be looked up this way when their keys
+/// freeze to themselves; their entries are found by key in the unfrozen
+/// collection.
+pub trait FrozenLookup: Freezable {
+    /// Hash this value. Values with equal frozen forms must hash the same.
+    fn hash_frozen<H: Hasher>(&self, state: &mut H);
+
+    /// Whether freezing this value would give a value equal to `frozen`.
+    fn eq_frozen(&self, frozen: &Self::Frozen) -> bool;
+}
+
+/// Hashes a value with [`FrozenLookup::hash_frozen`].
+pub(crate) struct Lookup<'a, T: ?Sized>(pub(crate) &'a T);
+impl<T: FrozenLookup> Hash for Lookup<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_frozen(state);
+    }
+}
+
+macro_rules! impl_self_lookup {
+    ($($impl_type:ty),*) => {
+        $(
+            impl FrozenLookup for $impl_type {
+                fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+                    self.hash(state);
+                }
+
+                fn eq_frozen(&self, frozen: &Self) -> bool {
+                    self == frozen
+                }
+            }
+        )*
+    };
+}
+impl_self_lookup!(
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    &str,
+    Duration,
+    String,
+    FrozenStr,
+    FrozenValue
+);
+
+impl<T: Freezable + ?Sized> FrozenLookup for Frozen<T>
+where
+    T::Frozen: Hash + PartialEq,
+{
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+
+    fn eq_frozen(&self, frozen: &T::Frozen) -> bool {
+        self.0 == *frozen
+    }
+}
+impl<T: FrozenLookup> FrozenLookup for Option<T> {
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        state.write_u8(self.is_some().into());
+        if let Some(value) = self {
+            value.hash_frozen(state);
+        }
+    }
+
+    fn eq_frozen(&self, frozen: &Option<Frozen<T>>) -> bool {
+        match (self, frozen) {
+            (Some(value), Some(frozen)) => value.eq_frozen(frozen),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+impl<T: FrozenLookup, const N: usize> FrozenLookup for [T; N] {
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        for item in self {
+            item.hash_frozen(state);
+        }
+    }
+
+    fn eq_frozen(&self, frozen: &[Frozen<T>; N]) -> bool {
+        self.iter()
+            .zip(frozen)
+            .all(|(item, frozen)| item.eq_frozen(frozen))
+    }
+}
+impl<T: FrozenLookup> FrozenLookup for Box<T> {
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        (**self).hash_frozen(state);
+    }
+
+    fn eq_frozen(&self, frozen: &T::Frozen) -> bool {
+        (**self).eq_frozen(frozen)
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($param:ident $index:tt),*) => {
+        impl<$($param: FrozenLookup),*> FrozenLookup for ($($param),*) {
+            #[allow(unused_variables)]
+            fn hash_frozen<S: Hasher>(&self, state: &mut S) {
+                $(self.$index.hash_frozen(state);)*
+            }
+
+            #[allow(unused_variables)]
+            fn eq_frozen(&self, frozen: &Self::Frozen) -> bool {
+                true $(&& self.$index.eq_frozen(&frozen.$index))*
+            }
+        }
+    };
+}
+tuple_impl!();
+tuple_impl!(A 0, B 1);
+tuple_impl!(A 0, B 1, C 2);
+tuple_impl!(A 0, B 1, C 2, D 3);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+/// Hash a sequence which freezes to `Vec<Frozen<T>>`.
+fn hash_sequence<'a, T: FrozenLookup + 'a, H: Hasher>(
+    items: impl ExactSizeIterator<Item = &'a T>,
+    state: &mut H,
+) {
+    state.write_usize(items.len());
+    for item in items {
+        item.hash_frozen(state);
+    }
+}
+/// Compare a sequence with its frozen form, `Vec<Frozen<T>>`.
+fn eq_sequence<'a, T: FrozenLookup + 'a>(
+    items: impl ExactSizeIterator<Item = &'a T>,
+    frozen: &[Frozen<T>],
+) -> bool {
+    items.len() == frozen.len()
+        && items
+            .zip(frozen)
+            .all(|(item, frozen)| item.eq_frozen(frozen))
+}
+
+macro_rules! impl_sequence_lookup {
+    ($($params:ident $(: $bound:path)?),* => $impl_type:ty) => {
+        impl<$($params: FrozenLookup $(+ $bound)?),*> FrozenLookup for $impl_type {
+            fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+                hash_sequence(self.iter(), state);
+            }
+
+            fn eq_frozen(&self, frozen: &Self::Frozen) -> bool {
+                eq_sequence(self.iter(), frozen)
+            }
+        }
+    };
+}
+impl_sequence_lookup!(T => Vec<T>);
+impl_sequence_lookup!(T => VecDeque<T>);
+impl_sequence_lookup!(T => LinkedList<T>);
+impl_sequence_lookup!(T: Ord => BTreeSet<T>);
+
+impl<K: FrozenLookup + Ord, V: FrozenLookup> FrozenLookup for BTreeMap<K, V> {
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for (key, value) in self {
+            key.hash_frozen(state);
+            value.hash_frozen(state);
+        }
+    }
+
+    fn eq_frozen(&self, frozen: &Vec<Frozen<(K, V)>>) -> bool {
+        self.len() == frozen.len()
+            && self.iter().zip(frozen).all(|((key, value), frozen)| {
+                let (frozen_key, frozen_value) = &**frozen;
+                key.eq_frozen(frozen_key) && value.eq_frozen(frozen_value)
+            })
+    }
+}
+
+/// Hash the entries of a map in any order.
+pub(crate) fn hash_map_entries<'a, K, V, H>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    state: &mut H,
+) where
+    K: FrozenLookup + 'a,
+    V: FrozenLookup + 'a,
+    H: Hasher,
+{
+    hash_unordered(
+        entries.map(|(key, value)| (Lookup(key), Lookup(value))),
+        state,
+    );
+}
+
+impl<T, S> FrozenLookup for HashSet<T, S>
+where
+    T: FreezesToSelf + FrozenLookup + Hash + Eq + DeepImmutable,
+    S: BuildHasher + Clone,
+{
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.iter().map(Lookup), state);
+    }
+
+    fn eq_frozen(&self, frozen: &Self::Frozen) -> bool {
+        self.len() == frozen.len() && frozen.iter().all(|item| self.contains(&**item))
+    }
+}
+impl<K, V, S> FrozenLookup for HashMap<K, V, S>
+where
+    K: FreezesToSelf + FrozenLookup + Hash + Eq + DeepImmutable,
+    V: FrozenLookup,
+    S: BuildHasher + Clone,
+{
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        hash_map_entries(self.iter(), state);
+    }
+
+    fn eq_frozen(&self, frozen: &Self::Frozen) -> bool {
+        self.len() == frozen.len()
+            && frozen.iter().all(|(key, frozen)| {
+                self.get(&**key)
+                    .is_some_and(|value| value.eq_frozen(frozen))
+            })
+    }
+}
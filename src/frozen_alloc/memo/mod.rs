@@ -0,0 +1,397 @@
+//! Caching the results of functions, keyed by their frozen arguments.
+//!
+//! Frozen collections can be hashed and compared, so functions over maps,
+//! sets and other collections can be memoized like functions over numbers.
+//! A cache stores each argument frozen, and is searched with the arguments
+//! as they were passed through [`FrozenLookup`], so a cache hit neither
+//! freezes nor allocates.
+//!
+//! [`Memo`] wraps a function in a cache owned by the caller. [`LocalMemo`]
+//! can be called through a shared reference, including recursively from the
+//! function it wraps, and [`SharedMemo`] can also be shared between threads.
+//! Each can hold every result, or be bounded, evicting the least recently
+//! used result when full. The [`memoize!`](crate::memoize) macro memoizes a
+//! function definition.
+//!
+//! ```
+//! use std::collections::HashSet;
+//!
+//! use freezable::memo::Memo;
+//!
+//! let mut calls = 0;
+//! let mut total = Memo::new(|set: &HashSet<u32>| {
+//!     calls += 1;
+//!     set.iter().sum::<u32>()
+//! });
+//! assert_eq!(total.call(HashSet::from([1, 2, 3])), 6);
+//! assert_eq!(total.call(HashSet::from([3, 2, 1])), 6);
+//! drop(total);
+//! assert_eq!(calls, 1);
+//! ```
+mod lookup;
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::hash::BuildHasher;
+#[cfg(not(feature = "std"))]
+use core::hash::BuildHasherDefault;
+
+use hashbrown::HashTable;
+#[cfg(feature = "std")]
+pub(crate) use lookup::hash_map_entries;
+pub use lookup::FrozenLookup;
+pub(crate) use lookup::Lookup;
+
+#[cfg(not(feature = "std"))]
+use crate::fnv::Fnv;
+#[cfg(feature = "std")]
+pub use crate::frozen_std::memo::SharedMemo;
+use crate::{Freezable, Frozen};
+
+#[cfg(feature = "std")]
+type DefaultState = std::hash::RandomState;
+#[cfg(not(feature = "std"))]
+type DefaultState = BuildHasherDefault<Fnv>;
+
+/// No entry, in the links between entries.
+const NIL: usize = usize::MAX;
+
+struct Entry<A: Freezable, R> {
+    key: Frozen<A>,
+    value: R,
+    hash: u64,
+    /// The next more recently used entry.
+    newer: usize,
+    /// The next less recently used entry.
+    older: usize,
+}
+
+/// The cache behind every memo: a hash table of entries, which are also
+/// linked from the most to the least recently used.
+pub(crate) struct Cache<A: Freezable, R> {
+    table: HashTable<usize>,
+    entries: Vec<Entry<A, R>>,
+    newest: usize,
+    oldest: usize,
+    limit: Option<usize>,
+    state: DefaultState,
+}
+impl<A: FrozenLookup, R> Cache<A, R> {
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Cache {
+            table: HashTable::new(),
+            entries: Vec::new(),
+            newest: NIL,
+            oldest: NIL,
+            limit,
+            state: DefaultState::default(),
+        }
+    }
+
+    pub(crate) fn hash(&self, args: &A) -> u64 {
+        self.state.hash_one(Lookup(args))
+    }
+
+    fn find(&self, hash: u64, args: &A) -> Option<usize> {
+        self.table
+            .find(hash, |&index| args.eq_frozen(&self.entries[index].key))
+            .copied()
+    }
+
+    pub(crate) fn get(&self, hash: u64, args: &A) -> Option<&R> {
+        self.find(hash, args)
+            .map(|index| &self.entries[index].value)
+    }
+
+    /// Look up `args`, marking the entry as the most recently used.
+    pub(crate) fn touch(&mut self, hash: u64, args: &A) -> Option<&R> {
+        let index = self.find(hash, args)?;
+        self.unlink(index);
+        self.link_newest(index);
+        Some(&self.entries[index].value)
+    }
+
+    /// Insert a result, unless the arguments are already cached, evicting the
+    /// least recently used entry if the cache is full.
+    pub(crate) fn insert(&mut self, hash: u64, args: A, value: R) {
+        if self.limit == Some(0) || self.find(hash, &args).is_some() {
+            return;
+        }
+        let entry = Entry {
+            key: args.freeze(),
+            value,
+            hash,
+            newer: NIL,
+            older: NIL,
+        };
+        let index = if Some(self.entries.len()) == self.limit {
+            let index = self.oldest;
+            let entries = &self.entries;
+            self.table
+                .find_entry(entries[index].hash, |&other| other == index)
+                .expect("cached entries are in the table")
+                .remove();
+            self.unlink(index);
+            self.entries[index] = entry;
+            index
+        } else {
+            self.entries.push(entry);
+            self.entries.len() - 1
+        };
+        self.link_newest(index);
+        let entries = &self.entries;
+        self.table
+            .insert_unique(hash, index, |&index| entries[index].hash);
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let Entry { newer, older, .. } = self.entries[index];
+        match newer {
+            NIL => self.newest = older,
+            newer => self.entries[newer].older = older,
+        }
+        match older {
+            NIL => self.oldest = newer,
+            older => self.entries[older].newer = newer,
+        }
+    }
+
+    fn link_newest(&mut self, index: usize) {
+        self.entries[index].newer = NIL;
+        self.entries[index].older = self.newest;
+        match self.newest {
+            NIL => self.oldest = index,
+            newest => self.entries[newest].newer = index,
+        }
+        self.newest = index;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.table.clear();
+        self.entries.clear();
+        self.newest = NIL;
+        self.oldest = NIL;
+    }
+}
+
+/// A function whose results are cached by its frozen arguments.
+///
+/// Calling the memo with arguments it has seen before returns a clone of the
+/// cached result, without calling the function.
+pub struct Memo<F, A: Freezable, R> {
+    function: F,
+    cache: Cache<A, R>,
+}
+impl<F: FnMut(&A) -> R, A: FrozenLookup, R> Memo<F, A, R> {
+    /// Cache every result of `function`.
+    pub fn new(function: F) -> Self {
+        Memo {
+            function,
+            cache: Cache::new(None),
+        }
+    }
+
+    /// Cache up to `capacity` results of `function`, evicting the least
+    /// recently used result when full.
+    pub fn bounded(function: F, capacity: usize) -> Self {
+        Memo {
+            function,
+            cache: Cache::new(Some(capacity)),
+        }
+    }
+
+    /// Call the function with `args`, or return the cached result.
+    pub fn call(&mut self, args: A) -> R
+    where
+        R: Clone,
+    {
+        let hash = self.cache.hash(&args);
+        if let Some(result) = self.cache.touch(hash, &args) {
+            return result.clone();
+        }
+        let result = (self.function)(&args);
+        self.cache.insert(hash, args, result.clone());
+        result
+    }
+
+    /// The cached result for `args`, if there is one. This does not count as
+    /// a use of the result.
+    pub fn get(&self, args: &A) -> Option<&R> {
+        self.cache.get(self.cache.hash(args), args)
+    }
+
+    /// The number of cached results.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Whether no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every cached result.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+/// A memoized function which can be called through a shared reference.
+///
+/// The cache is not borrowed while the function runs, so the function may
+/// call the memo recursively. This is the memo used by
+/// [`memoize!`](crate::memoize) for each thread.
+pub struct LocalMemo<F, A: Freezable, R> {
+    function: F,
+    cache: RefCell<Cache<A, R>>,
+}
+impl<F: Fn(&A) -> R, A: FrozenLookup, R: Clone> LocalMemo<F, A, R> {
+    /// Cache every result of `function`.
+    pub fn new(function: F) -> Self {
+        LocalMemo {
+            function,
+            cache: RefCell::new(Cache::new(None)),
+        }
+    }
+
+    /// Cache up to `capacity` results of `function`, evicting the least
+    /// recently used result when full.
+    pub fn bounded(function: F, capacity: usize) -> Self {
+        LocalMemo {
+            function,
+            cache: RefCell::new(Cache::new(Some(capacity))),
+        }
+    }
+
+    /// Call the function with `args`, or return the cached result.
+    pub fn call(&self, args: A) -> R {
+        let hash = self.cache.borrow().hash(&args);
+        if let Some(result) = self.cache.borrow_mut().touch(hash, &args) {
+            return result.clone();
+        }
+        let result = (self.function)(&args);
+        self.cache.borrow_mut().insert(hash, args, result.clone());
+        result
+    }
+
+    /// The cached result for `args`, if there is one. This does not count as
+    /// a use of the result.
+    pub fn get(&self, args: &A) -> Option<R> {
+        let cache = self.cache.borrow();
+        cache.get(cache.hash(args), args).cloned()
+    }
+
+    /// The number of cached results.
+    pub fn len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Whether no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every cached result.
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::{Cell, RefCell};
+
+    use super::*;
+
+    /// The cached arguments, from the most to the least recently used.
+    fn order(cache: &Cache<u32, u32>) -> Vec<u32> {
+        let mut order = Vec::new();
+        let mut index = cache.newest;
+        while index != NIL {
+            order.push(*cache.entries[index].key);
+            index = cache.entries[index].older;
+        }
+        // The links must agree in both directions.
+        let mut index = cache.oldest;
+        for &args in order.iter().rev() {
+            assert_eq!(*cache.entries[index].key, args);
+            index = cache.entries[index].newer;
+        }
+        assert_eq!(index, NIL);
+        order
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = Cache::new(Some(3));
+        for args in 1..=3 {
+            cache.insert(cache.hash(&args), args, args * 10);
+        }
+        assert_eq!(order(&cache), [3, 2, 1]);
+
+        assert_eq!(cache.touch(cache.hash(&1), &1), Some(&10));
+        assert_eq!(order(&cache), [1, 3, 2]);
+        // Looking a result up without touching it does not save it.
+        assert_eq!(cache.get(cache.hash(&2), &2), Some(&20));
+        assert_eq!(order(&cache), [1, 3, 2]);
+
+        cache.insert(cache.hash(&4), 4, 40);
+        assert_eq!(order(&cache), [4, 1, 3]);
+        assert_eq!(cache.get(cache.hash(&2), &2), None);
+        cache.insert(cache.hash(&5), 5, 50);
+        assert_eq!(order(&cache), [5, 4, 1]);
+        assert_eq!(cache.len(), 3);
+
+        // Inserting cached arguments again keeps the first result.
+        cache.insert(cache.hash(&4), 4, 0);
+        assert_eq!(cache.get(cache.hash(&4), &4), Some(&40));
+        assert_eq!(order(&cache), [5, 4, 1]);
+
+        cache.clear();
+        assert_eq!(order(&cache), []);
+        cache.insert(cache.hash(&6), 6, 60);
+        assert_eq!(order(&cache), [6]);
+    }
+
+    #[test]
+    fn touching_the_only_entry() {
+        let mut cache = Cache::new(Some(1));
+        cache.insert(cache.hash(&1), 1, 10);
+        assert_eq!(cache.touch(cache.hash(&1), &1), Some(&10));
+        assert_eq!(order(&cache), [1]);
+        cache.insert(cache.hash(&2), 2, 20);
+        assert_eq!(order(&cache), [2]);
+        assert_eq!(cache.touch(cache.hash(&1), &1), None);
+    }
+
+    #[test]
+    fn bounded_memo_calls_again_after_eviction() {
+        let calls = RefCell::new(Vec::new());
+        let mut memo = Memo::bounded(
+            |&args: &u32| {
+                calls.borrow_mut().push(args);
+                args + 1
+            },
+            2,
+        );
+        for args in [1, 2, 1, 3, 2, 1] {
+            assert_eq!(memo.call(args), args + 1);
+        }
+        // 3 evicts 2, as 1 was used more recently, and then 2 evicts 1.
+        assert_eq!(memo.len(), 2);
+        drop(memo);
+        assert_eq!(*calls.borrow(), [1, 2, 3, 2, 1]);
+
+        let calls = Cell::new(0);
+        let mut memo = Memo::bounded(|_: &u32| calls.set(calls.get() + 1), 0);
+        memo.call(1);
+        memo.call(1);
+        assert!(memo.is_empty());
+        drop(memo);
+        assert_eq!(calls.get(), 2);
+    }
+}
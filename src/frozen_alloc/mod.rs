@@ -0,0 +1,14 @@
+pub mod archive;
+pub mod boxed;
+pub mod canonical;
+pub mod collections;
+pub mod convert;
+pub mod diff;
+pub mod heap_size;
+pub mod history;
+#[cfg(feature = "hashbrown")]
+pub mod memo;
+pub mod string;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod value;
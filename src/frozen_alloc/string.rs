@@ -0,0 +1,13 @@
+use alloc::string::String;
+
+use crate::{
+    impl_self_freezable,
+    CloneUnfreezable,
+    Freezable,
+    FreezesToSelf,
+    Frozen,
+    SelfFrozen,
+    Unfreezable,
+};
+
+impl_self_freezable!(String);
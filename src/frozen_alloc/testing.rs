@@ -0,0 +1,71 @@
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "hashbrown")]
+use core::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::{HashMap, HashSet};
+
+use crate::testing::{Arbitrary, Rng};
+
+/// Generate up to [`Rng::size`] elements and collect them.
+pub(crate) fn arbitrary_collection<C: FromIterator<T>, T: Arbitrary>(
+    rng: &mut Rng,
+) -> C {
+    let len = rng.collection_len();
+    (0..len).map(|_| rng.arbitrary()).collect()
+}
+
+macro_rules! impl_arbitrary_collection {
+    ($($params:ident $(: $bound:path)?),* => $impl_type:ty, $element:ty) => {
+        impl<$($params: Arbitrary $(+ $bound)?),*> Arbitrary for $impl_type {
+            fn arbitrary(rng: &mut Rng) -> Self {
+                arbitrary_collection::<_, $element>(rng)
+            }
+        }
+    };
+}
+impl_arbitrary_collection!(=> String, char);
+impl_arbitrary_collection!(T => Vec<T>, T);
+impl_arbitrary_collection!(T => VecDeque<T>, T);
+impl_arbitrary_collection!(T => LinkedList<T>, T);
+impl_arbitrary_collection!(T: Ord => BinaryHeap<T>, T);
+impl_arbitrary_collection!(T: Ord => BTreeSet<T>, T);
+impl_arbitrary_collection!(K: Ord, V => BTreeMap<K, V>, (K, V));
+
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + Arbitrary, S: BuildHasher + Default> Arbitrary for HashSet<T, S> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        arbitrary_collection(rng)
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + Arbitrary, V: Arbitrary, S: BuildHasher + Default> Arbitrary
+    for HashMap<K, V, S>
+{
+    fn arbitrary(rng: &mut Rng) -> Self {
+        arbitrary_collection(rng)
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Box<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        Box::new(rng.arbitrary())
+    }
+}
+impl<T: Arbitrary> Arbitrary for Rc<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        Rc::new(rng.arbitrary())
+    }
+}
+#[cfg(target_has_atomic = "ptr")]
+impl<T: Arbitrary> Arbitrary for Arc<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        Arc::new(rng.arbitrary())
+    }
+}
@@ -0,0 +1,496 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter, Write};
+
+use super::{FrozenArray, FrozenStr, FrozenValue, FrozenValueMap};
+
+/// How deeply arrays and objects may be nested, to bound the recursion of
+/// the parser.
+const MAX_DEPTH: usize = 128;
+
+/// Why a JSON document could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JsonError {
+    /// The document ended in the middle of a value.
+    UnexpectedEnd,
+    /// A character which cannot appear here.
+    Unexpected {
+        /// The byte offset of the character.
+        pos: usize,
+        /// The character.
+        found: char,
+    },
+    /// A number which is malformed, or too large to represent.
+    InvalidNumber {
+        /// The byte offset where the number starts.
+        pos: usize,
+    },
+    /// A malformed escape sequence in a string.
+    InvalidEscape {
+        /// The byte offset of the backslash.
+        pos: usize,
+    },
+    /// Arrays and objects are nested too deeply.
+    TooDeep {
+        /// The byte offset of the array or object which is too deep.
+        pos: usize,
+    },
+    /// The document continues after its value.
+    TrailingCharacters {
+        /// The byte offset where the extra characters start.
+        pos: usize,
+    },
+}
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::UnexpectedEnd => write!(f, "unexpected end of JSON"),
+            JsonError::Unexpected { pos, found } => {
+                write!(f, "unexpected {found:?} in JSON at {pos}")
+            }
+            JsonError::InvalidNumber { pos } => {
+                write!(f, "invalid JSON number at {pos}")
+            }
+            JsonError::InvalidEscape { pos } => {
+                write!(f, "invalid JSON escape at {pos}")
+            }
+            JsonError::TooDeep { pos } => {
+                write!(f, "JSON nested more than {MAX_DEPTH} levels deep at {pos}")
+            }
+            JsonError::TrailingCharacters { pos } => {
+                write!(f, "trailing characters after JSON at {pos}")
+            }
+        }
+    }
+}
+impl Error for JsonError {
+}
+
+pub(super) fn parse(text: &str) -> Result<FrozenValue, JsonError> {
+    let mut parser = Parser {
+        text,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(JsonError::TrailingCharacters { pos: parser.pos });
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn unexpected(&self) -> JsonError {
+        match self.text[self.pos..].chars().next() {
+            Some(found) => JsonError::Unexpected {
+                pos: self.pos,
+                found,
+            },
+            None => JsonError::UnexpectedEnd,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn literal(
+        &mut self,
+        literal: &str,
+        value: FrozenValue,
+    ) -> Result<FrozenValue, JsonError> {
+        for &byte in literal.as_bytes() {
+            self.expect(byte)?;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<FrozenValue, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.literal("null", FrozenValue::Null),
+            Some(b't') => self.literal("true", FrozenValue::Bool(true)),
+            Some(b'f') => self.literal("false", FrozenValue::Bool(false)),
+            Some(b'"') => Ok(FrozenValue::String(self.string()?)),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<FrozenValue, JsonError>,
+    ) -> Result<FrozenValue, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(JsonError::TooDeep { pos: self.pos });
+        }
+        self.depth += 1;
+        let value = parse(self)?;
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn array(&mut self) -> Result<FrozenValue, JsonError> {
+        self.expect(b'[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(FrozenValue::Array(FrozenArray::default()));
+        }
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(FrozenValue::Array(elements.into()));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<FrozenValue, JsonError> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(FrozenValue::Map(FrozenValueMap::default()));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected());
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(FrozenValue::Map(entries.into_iter().collect()));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<FrozenStr, JsonError> {
+        self.expect(b'"')?;
+        let mut string = String::new();
+        loop {
+            let start = self.pos;
+            while let Some(byte) = self.peek() {
+                if matches!(byte, b'"' | b'\\') || byte < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            // The loop only stops at ASCII bytes, so this is on a character
+            // boundary.
+            string.push_str(&self.text[start..self.pos]);
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(string.into());
+                }
+                Some(b'\\') => string.push(self.escape()?),
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let start = self.pos;
+        let invalid = JsonError::InvalidEscape { pos: start };
+        self.pos += 1;
+        let Some(byte) = self.peek() else {
+            return Err(JsonError::UnexpectedEnd);
+        };
+        self.pos += 1;
+        Ok(match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4().ok_or(invalid)?;
+                if (0xd800..0xdc00).contains(&high) {
+                    if !self.text[self.pos..].starts_with("\\u") {
+                        return Err(invalid);
+                    }
+                    self.pos += 2;
+                    let low = self.hex4().ok_or(invalid)?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(invalid);
+                    }
+                    let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                    char::from_u32(code).ok_or(invalid)?
+                } else {
+                    char::from_u32(high).ok_or(invalid)?
+                }
+            }
+            _ => return Err(invalid),
+        })
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.pos..self.pos + 4)?;
+        if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn number(&mut self) -> Result<FrozenValue, JsonError> {
+        let start = self.pos;
+        let invalid = JsonError::InvalidNumber { pos: start };
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let int_start = self.pos;
+        match self.digits() {
+            0 => return Err(invalid),
+            1 => {}
+            _ if self.text.as_bytes()[int_start] == b'0' => return Err(invalid),
+            _ => {}
+        }
+        let mut integral = true;
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            integral = false;
+            if self.digits() == 0 {
+                return Err(invalid);
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            integral = false;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                return Err(invalid);
+            }
+        }
+        let text = &self.text[start..self.pos];
+        if integral {
+            if let Ok(value) = text.parse() {
+                return Ok(FrozenValue::Integer(value));
+            }
+        }
+        match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(FrozenValue::Float(value)),
+            _ => Err(invalid),
+        }
+    }
+}
+
+pub(super) fn write(value: &FrozenValue, f: &mut Formatter<'_>) -> fmt::Result {
+    match value {
+        FrozenValue::Null => f.write_str("null"),
+        FrozenValue::Bool(value) => write!(f, "{value}"),
+        FrozenValue::Integer(value) => write!(f, "{value}"),
+        // `Debug` always writes a fraction or exponent, so the float is read
+        // back as a float.
+        FrozenValue::Float(value) if value.is_finite() => write!(f, "{value:?}"),
+        FrozenValue::Float(_) => f.write_str("null"),
+        FrozenValue::String(value) => write_string(value, f),
+        FrozenValue::Array(array) => {
+            f.write_char('[')?;
+            for (i, element) in array.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                write(element, f)?;
+            }
+            f.write_char(']')
+        }
+        FrozenValue::Map(map) => {
+            f.write_char('{')?;
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                write_string(key, f)?;
+                f.write_char(':')?;
+                write(value, f)?;
+            }
+            f.write_char('}')
+        }
+    }
+}
+
+fn write_string(string: &str, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_char('"')?;
+    let mut start = 0;
+    for (i, c) in string.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            '\0'..='\u{1f}' => "",
+            _ => continue,
+        };
+        f.write_str(&string[start..i])?;
+        if escape.is_empty() {
+            write!(f, "\\u{:04x}", u32::from(c))?;
+        } else {
+            f.write_str(escape)?;
+        }
+        start = i + c.len_utf8();
+    }
+    f.write_str(&string[start..])?;
+    f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+
+    fn parse_string(json: &str) -> Result<String, JsonError> {
+        match parse(json)? {
+            FrozenValue::String(string) => Ok(string.to_string()),
+            value => panic!("expected a string, got {value:?}"),
+        }
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            parse_string(r#""\"\\\/\b\f\n\r\tAé""#).unwrap(),
+            "\"\\/\u{8}\u{c}\n\r\tAé"
+        );
+        assert_eq!(
+            parse_string(r#""\x""#),
+            Err(JsonError::InvalidEscape { pos: 1 })
+        );
+        assert_eq!(
+            parse_string(r#""a\u12g4""#),
+            Err(JsonError::InvalidEscape { pos: 2 })
+        );
+        assert_eq!(
+            parse_string(r#""\u12""#),
+            Err(JsonError::InvalidEscape { pos: 1 })
+        );
+        assert_eq!(parse_string("\"\\"), Err(JsonError::UnexpectedEnd));
+        assert_eq!(
+            parse_string("\"a\nb\""),
+            Err(JsonError::Unexpected {
+                pos: 2,
+                found: '\n'
+            })
+        );
+
+        let string = "\"\\/\u{8}\u{c}\n\r\t\u{0}\u{1f}é";
+        let json = FrozenValue::String(string.into()).to_json();
+        assert_eq!(json, r#""\"\\/\b\f\n\r\t\u0000\u001fé""#);
+        assert_eq!(parse_string(&json).unwrap(), string);
+    }
+
+    #[test]
+    fn surrogates() {
+        assert_eq!(parse_string(r#""\ud83d\ude00""#).unwrap(), "😀");
+        assert_eq!(parse_string(r#""\uD83D\uDE00!""#).unwrap(), "😀!");
+        // A high surrogate must be followed by a low one, and a low one
+        // cannot appear on its own.
+        for json in [
+            r#""\ud83d""#,
+            r#""\ud83dx""#,
+            r#""\ud83d\u0041""#,
+            r#""\ud83d\ud83d""#,
+            r#""\ude00""#,
+        ] {
+            assert_eq!(
+                parse_string(json),
+                Err(JsonError::InvalidEscape { pos: 1 }),
+                "{json}"
+            );
+        }
+        // Characters outside the basic plane are written as themselves.
+        assert_eq!(FrozenValue::String("😀".into()).to_json(), "\"😀\"");
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_DEPTH + 1)),
+            Err(JsonError::TooDeep { pos: MAX_DEPTH })
+        );
+
+        // Objects count towards the same limit, and closing a level frees it
+        // up again.
+        let objects =
+            r#"{"a":"#.repeat(MAX_DEPTH / 2) + "null" + &"}".repeat(MAX_DEPTH / 2);
+        let mixed = "[".repeat(MAX_DEPTH / 2) + &objects + &"]".repeat(MAX_DEPTH / 2);
+        assert!(parse(&mixed).is_ok());
+        let siblings =
+            "[".to_string() + &vec![nested(MAX_DEPTH - 1); 3].join(",") + "]";
+        assert!(parse(&siblings).is_ok());
+        let too_deep =
+            "[".repeat(MAX_DEPTH / 2 + 1) + &objects + &"]".repeat(MAX_DEPTH / 2 + 1);
+        assert!(matches!(parse(&too_deep), Err(JsonError::TooDeep { .. })));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let value = parse(r#"{"a": 1, "b": 2, "a": 3, "a": 4}"#).unwrap();
+        let map = value.as_map().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&FrozenValue::Integer(4)));
+        assert_eq!(value.to_json(), r#"{"a":4,"b":2}"#);
+        assert_eq!(value, parse(r#"{"b": 2, "a": 4}"#).unwrap());
+    }
+}
@@ -0,0 +1,520 @@
+//! Dynamically typed frozen trees, such as parsed JSON documents.
+//!
+//! A [`FrozenValue`] is null, a boolean, a number, a string, an array, or a
+//! map from strings to values. Values are immutable once built, and are
+//! hashable and totally ordered, so whole documents can be used as map keys
+//! or deduplicated. [`FrozenValue::from_json`] parses JSON straight into a
+//! frozen tree, and the [`Display`] implementation writes it back as
+//! canonical JSON, with map keys sorted and no whitespace.
+//!
+//! ```
+//! use freezable::value::{FrozenValue, PathSegment};
+//!
+//! let config = FrozenValue::from_json(r#"{"b": [{"c": 1.5}], "a": null}"#).unwrap();
+//! assert_eq!(config.to_json(), r#"{"a":null,"b":[{"c":1.5}]}"#);
+//!
+//! let path = [PathSegment::from("b"), 0.into(), "c".into()];
+//! assert_eq!(config.get_path(path).and_then(FrozenValue::as_f64), Some(1.5));
+//! assert_eq!(config.get_path(["a"]), Some(&FrozenValue::Null));
+//! assert_eq!(config.get_path(["missing"]), None);
+//! ```
+mod json;
+
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::slice;
+
+pub use json::JsonError;
+
+use crate::{
+    impl_self_freezable,
+    CloneUnfreezable,
+    Freezable,
+    FreezesToSelf,
+    Frozen,
+    SelfFrozen,
+    Unfreezable,
+};
+
+/// An immutable string.
+///
+/// Unlike a [`String`], this holds no spare capacity, and it cannot be
+/// changed once created.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FrozenStr(pub(crate) Box<str>);
+impl FrozenStr {
+    /// The string as a `str`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+impl Deref for FrozenStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+impl AsRef<str> for FrozenStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+impl Borrow<str> for FrozenStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+impl From<&str> for FrozenStr {
+    fn from(value: &str) -> Self {
+        FrozenStr(value.into())
+    }
+}
+impl From<String> for FrozenStr {
+    fn from(value: String) -> Self {
+        FrozenStr(value.into_boxed_str())
+    }
+}
+impl From<FrozenStr> for String {
+    fn from(value: FrozenStr) -> Self {
+        value.0.into_string()
+    }
+}
+impl PartialEq<str> for FrozenStr {
+    fn eq(&self, other: &str) -> bool {
+        *self.0 == *other
+    }
+}
+impl PartialEq<&str> for FrozenStr {
+    fn eq(&self, other: &&str) -> bool {
+        *self.0 == **other
+    }
+}
+impl Debug for FrozenStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+impl Display for FrozenStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.0, f)
+    }
+}
+impl_self_freezable!(FrozenStr);
+/// Thaws into an owned copy of the string.
+impl Unfreezable<FrozenStr> for String {
+    fn thaw(wrapped: FrozenStr) -> Self {
+        wrapped.into()
+    }
+}
+/// Thaws into an owned copy of the string.
+impl CloneUnfreezable<FrozenStr> for String {
+    fn thaw_cloned(wrapped: &FrozenStr) -> Self {
+        wrapped.as_str().to_owned()
+    }
+}
+
+/// An immutable array of [`FrozenValue`]s.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FrozenArray(pub(crate) Box<[FrozenValue]>);
+impl FrozenArray {
+    /// The elements as a slice.
+    pub fn as_slice(&self) -> &[FrozenValue] {
+        &self.0
+    }
+}
+impl Deref for FrozenArray {
+    type Target = [FrozenValue];
+
+    fn deref(&self) -> &[FrozenValue] {
+        &self.0
+    }
+}
+impl FromIterator<FrozenValue> for FrozenArray {
+    fn from_iter<I: IntoIterator<Item = FrozenValue>>(iter: I) -> Self {
+        FrozenArray(iter.into_iter().collect())
+    }
+}
+impl From<Vec<FrozenValue>> for FrozenArray {
+    fn from(value: Vec<FrozenValue>) -> Self {
+        FrozenArray(value.into_boxed_slice())
+    }
+}
+impl<'a> IntoIterator for &'a FrozenArray {
+    type IntoIter = slice::Iter<'a, FrozenValue>;
+    type Item = &'a FrozenValue;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+impl Debug for FrozenArray {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter()).finish()
+    }
+}
+
+/// An immutable map from strings to [`FrozenValue`]s.
+///
+/// Entries are kept sorted by key, and looked up by bisection.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FrozenValueMap(pub(crate) Box<[(FrozenStr, FrozenValue)]>);
+impl FrozenValueMap {
+    /// The number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value for `key`, if there is one.
+    pub fn get(&self, key: &str) -> Option<&FrozenValue> {
+        self.0
+            .binary_search_by(|(k, _)| k.as_str().cmp(key))
+            .ok()
+            .map(|index| &self.0[index].1)
+    }
+
+    /// Whether the map contains `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over the entries, sorted by key.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Iterate over the keys in sorted order.
+    pub fn keys(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &FrozenStr> + ExactSizeIterator {
+        self.0.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over the values, sorted by their keys.
+    pub fn values(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &FrozenValue> + ExactSizeIterator {
+        self.0.iter().map(|(_, value)| value)
+    }
+}
+/// When several entries have the same key, the last one is kept.
+impl<K: Into<FrozenStr>> FromIterator<(K, FrozenValue)> for FrozenValueMap {
+    fn from_iter<I: IntoIterator<Item = (K, FrozenValue)>>(iter: I) -> Self {
+        let mut entries: Vec<_> = iter
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect();
+        // Reversing first makes the stable sort and dedup keep the last entry
+        // for each key.
+        entries.reverse();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.dedup_by(|(a, _), (b, _)| a == b);
+        FrozenValueMap(entries.into_boxed_slice())
+    }
+}
+impl<'a> IntoIterator for &'a FrozenValueMap {
+    type IntoIter = Iter<'a>;
+    type Item = (&'a FrozenStr, &'a FrozenValue);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl Debug for FrozenValueMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`FrozenValueMap`], sorted by key.
+#[derive(Clone)]
+pub struct Iter<'a>(slice::Iter<'a, (FrozenStr, FrozenValue)>);
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a FrozenStr, &'a FrozenValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+impl ExactSizeIterator for Iter<'_> {
+}
+
+/// A dynamically typed, immutable value.
+///
+/// Values are totally ordered: first by kind, in the order the variants are
+/// declared, and then by their contents. Floats are compared with
+/// [`f64::total_cmp`], so every float is equal to itself, including NaN, and
+/// `-0.0` is less than `0.0`. Integers and floats are different kinds, so
+/// `1` and `1.0` are not equal.
+#[derive(Clone, Debug, Default)]
+pub enum FrozenValue {
+    /// The absence of a value.
+    #[default]
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Integer(i64),
+    /// A floating-point number.
+    Float(f64),
+    /// A string.
+    String(FrozenStr),
+    /// An array of values.
+    Array(FrozenArray),
+    /// A map from strings to values.
+    Map(FrozenValueMap),
+}
+impl FrozenValue {
+    /// Parse a JSON document into a frozen value.
+    ///
+    /// Numbers without a fraction or exponent become
+    /// [`Integer`](Self::Integer)s if they fit in an `i64`, and
+    /// [`Float`](Self::Float)s otherwise. When an object has several entries
+    /// with the same key, the last one is kept.
+    pub fn from_json(text: &str) -> Result<Self, JsonError> {
+        json::parse(text)
+    }
+
+    /// Write this value as canonical JSON. This is the same as the
+    /// [`Display`] output.
+    pub fn to_json(&self) -> String {
+        self.to_string()
+    }
+
+    /// Whether this is [`Null`](Self::Null).
+    pub fn is_null(&self) -> bool {
+        matches!(self, FrozenValue::Null)
+    }
+
+    /// The boolean, if this is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            FrozenValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The integer, if this is one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            FrozenValue::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number as a float, if this is an integer or a float.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            FrozenValue::Integer(value) => Some(value as f64),
+            FrozenValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The string, if this is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FrozenValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The array, if this is one.
+    pub fn as_array(&self) -> Option<&FrozenArray> {
+        match self {
+            FrozenValue::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The map, if this is one.
+    pub fn as_map(&self) -> Option<&FrozenValueMap> {
+        match self {
+            FrozenValue::Map(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Look up a single key of a map or index of an array.
+    pub fn get<'a>(&self, segment: impl Into<PathSegment<'a>>) -> Option<&FrozenValue> {
+        match (self, segment.into()) {
+            (FrozenValue::Map(map), PathSegment::Key(key)) => map.get(key),
+            (FrozenValue::Array(array), PathSegment::Index(index)) => array.get(index),
+            _ => None,
+        }
+    }
+
+    /// Follow a path of map keys and array indices down the tree.
+    ///
+    /// A path of only keys or only indices can be given directly, as in
+    /// `value.get_path(["a", "b"])`; paths mixing the two are written with
+    /// [`PathSegment`]s, as in
+    /// `value.get_path([PathSegment::from("a"), 0.into(), "b".into()])`.
+    pub fn get_path<'a, P: Into<PathSegment<'a>>>(
+        &self,
+        path: impl IntoIterator<Item = P>,
+    ) -> Option<&FrozenValue> {
+        path.into_iter()
+            .try_fold(self, |value, segment| value.get(segment))
+    }
+
+    /// The position of this kind of value in the ordering of kinds.
+    fn kind(&self) -> u8 {
+        match self {
+            FrozenValue::Null => 0,
+            FrozenValue::Bool(_) => 1,
+            FrozenValue::Integer(_) => 2,
+            FrozenValue::Float(_) => 3,
+            FrozenValue::String(_) => 4,
+            FrozenValue::Array(_) => 5,
+            FrozenValue::Map(_) => 6,
+        }
+    }
+}
+impl PartialEq for FrozenValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for FrozenValue {
+}
+impl PartialOrd for FrozenValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for FrozenValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (FrozenValue::Bool(a), FrozenValue::Bool(b)) => a.cmp(b),
+            (FrozenValue::Integer(a), FrozenValue::Integer(b)) => a.cmp(b),
+            (FrozenValue::Float(a), FrozenValue::Float(b)) => a.total_cmp(b),
+            (FrozenValue::String(a), FrozenValue::String(b)) => a.cmp(b),
+            (FrozenValue::Array(a), FrozenValue::Array(b)) => a.cmp(b),
+            (FrozenValue::Map(a), FrozenValue::Map(b)) => a.cmp(b),
+            _ => self.kind().cmp(&other.kind()),
+        }
+    }
+}
+/// Floats are hashed by their bits, which agrees with [`f64::total_cmp`].
+impl Hash for FrozenValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u8(self.kind());
+        match self {
+            FrozenValue::Null => {}
+            FrozenValue::Bool(value) => value.hash(state),
+            FrozenValue::Integer(value) => value.hash(state),
+            FrozenValue::Float(value) => value.to_bits().hash(state),
+            FrozenValue::String(value) => value.hash(state),
+            FrozenValue::Array(value) => value.hash(state),
+            FrozenValue::Map(value) => value.hash(state),
+        }
+    }
+}
+/// Writes canonical JSON: map keys in sorted order, no whitespace, and
+/// floats always with a fraction or exponent. Floats which JSON cannot
+/// represent (NaN and the infinities) are written as `null`.
+impl Display for FrozenValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        json::write(self, f)
+    }
+}
+impl core::str::FromStr for FrozenValue {
+    type Err = JsonError;
+
+    fn from_str(text: &str) -> Result<Self, JsonError> {
+        json::parse(text)
+    }
+}
+impl_self_freezable!(FrozenValue);
+
+impl From<bool> for FrozenValue {
+    fn from(value: bool) -> Self {
+        FrozenValue::Bool(value)
+    }
+}
+impl From<i64> for FrozenValue {
+    fn from(value: i64) -> Self {
+        FrozenValue::Integer(value)
+    }
+}
+impl From<f64> for FrozenValue {
+    fn from(value: f64) -> Self {
+        FrozenValue::Float(value)
+    }
+}
+impl From<&str> for FrozenValue {
+    fn from(value: &str) -> Self {
+        FrozenValue::String(value.into())
+    }
+}
+impl From<String> for FrozenValue {
+    fn from(value: String) -> Self {
+        FrozenValue::String(value.into())
+    }
+}
+impl From<FrozenStr> for FrozenValue {
+    fn from(value: FrozenStr) -> Self {
+        FrozenValue::String(value)
+    }
+}
+impl From<FrozenArray> for FrozenValue {
+    fn from(value: FrozenArray) -> Self {
+        FrozenValue::Array(value)
+    }
+}
+impl From<FrozenValueMap> for FrozenValue {
+    fn from(value: FrozenValueMap) -> Self {
+        FrozenValue::Map(value)
+    }
+}
+impl<T: Into<FrozenValue>> From<Option<T>> for FrozenValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(FrozenValue::Null, Into::into)
+    }
+}
+impl FromIterator<FrozenValue> for FrozenValue {
+    fn from_iter<I: IntoIterator<Item = FrozenValue>>(iter: I) -> Self {
+        FrozenValue::Array(iter.into_iter().collect())
+    }
+}
+
+/// One step of a path through a [`FrozenValue`]: a map key or an array index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment<'a> {
+    /// Look up a key in a map.
+    Key(&'a str),
+    /// Look up an index in an array.
+    Index(usize),
+}
+impl<'a> From<&'a str> for PathSegment<'a> {
+    fn from(key: &'a str) -> Self {
+        PathSegment::Key(key)
+    }
+}
+impl From<usize> for PathSegment<'_> {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
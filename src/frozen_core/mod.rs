@@ -1,7 +1,15 @@
-use core::time::Duration;
 use core::ops::Deref;
+use core::time::Duration;
 
-use crate::{impl_self_freezable, Freezable, Frozen, Unfreezable};
+use crate::{
+    impl_self_freezable,
+    CloneUnfreezable,
+    Freezable,
+    FreezesToSelf,
+    Frozen,
+    SelfFrozen,
+    Unfreezable,
+};
 macro_rules! tuple_impl {
     ($($param:ident),*) => {
         impl<$($param: Freezable),*> Freezable for ($($param),*) {
@@ -11,7 +19,7 @@ macro_rules! tuple_impl {
                 #[allow(non_snake_case)]
                 let ($($param),*) = self;
 
-                Frozen(($($param.freeze()),*))
+                Frozen::new(($($param.freeze()),*))
             }
         }
         #[allow(clippy::unused_unit)]
@@ -23,6 +31,15 @@ macro_rules! tuple_impl {
                 ($($param.thaw()),*)
             }
         }
+        #[allow(clippy::unused_unit)]
+        impl<$($param: Freezable + CloneUnfreezable<$param>),*> CloneUnfreezable<($($param),*)> for ($($param),*) {
+            fn thaw_cloned(wrapped: &<($($param),*) as Freezable>::Frozen) -> Self {
+                #[allow(non_snake_case)]
+                let ($($param),*) = wrapped;
+
+                ($($param.to_thawed()),*)
+            }
+        }
     };
 }
 tuple_impl!();
@@ -42,7 +59,7 @@ impl<T: Freezable, const N: usize> Freezable for [T; N] {
     type Frozen = [Frozen<T>; N];
 
     fn freeze(self) -> Frozen<Self> {
-        Frozen(self.map(Freezable::freeze))
+        Frozen::new(self.map(Freezable::freeze))
     }
 }
 impl<T: Unfreezable<U>, U: Freezable, const N: usize> Unfreezable<[U; N]> for [T; N] {
@@ -50,12 +67,19 @@ impl<T: Unfreezable<U>, U: Freezable, const N: usize> Unfreezable<[U; N]> for [T
         wrapped.map(Frozen::thaw)
     }
 }
+impl<T: CloneUnfreezable<U>, U: Freezable, const N: usize> CloneUnfreezable<[U; N]>
+    for [T; N]
+{
+    fn thaw_cloned(wrapped: &<[U; N] as Freezable>::Frozen) -> Self {
+        wrapped.each_ref().map(Frozen::to_thawed)
+    }
+}
 
 impl<T: Freezable> Freezable for Option<T> {
     type Frozen = Option<Frozen<T>>;
 
     fn freeze(self) -> Frozen<Self> {
-        Frozen(self.map(Freezable::freeze))
+        Frozen::new(self.map(Freezable::freeze))
     }
 }
 impl<T: Unfreezable<U>, U: Freezable> Unfreezable<Option<U>> for Option<T> {
@@ -63,6 +87,11 @@ impl<T: Unfreezable<U>, U: Freezable> Unfreezable<Option<U>> for Option<T> {
         wrapped.map(Frozen::thaw)
     }
 }
+impl<T: CloneUnfreezable<U>, U: Freezable> CloneUnfreezable<Option<U>> for Option<T> {
+    fn thaw_cloned(wrapped: &<Option<U> as Freezable>::Frozen) -> Self {
+        wrapped.as_ref().map(Frozen::to_thawed)
+    }
+}
 
 impl_self_freezable!(bool);
 impl_self_freezable!(char);
@@ -81,33 +110,33 @@ impl_self_freezable!(u64);
 impl_self_freezable!(u128);
 impl_self_freezable!(usize);
 impl_self_freezable!(&'a str, 'a);
+impl_self_freezable!(Duration);
 
-impl Freezable for Duration {
-    type Frozen = Self;
-
-    fn freeze(self) -> Frozen<Self> {
-        Frozen(self)
-    }
-}
-impl Unfreezable<Duration> for Duration {
-    fn thaw(wrapped: <Duration as Freezable>::Frozen) -> Self {
-        wrapped
-    }
-}
-
-// Freezable bound is used to enforce that the type does not contain `UnsafeCell`.
+// `&T` is only `DeepImmutable` if `T` is, so a frozen reference to a type with
+// interior mutability cannot be hashed or compared.
 impl<T: Freezable + ?Sized> Freezable for &T {
     type Frozen = Self;
 
+    const SELF_FROZEN: Option<SelfFrozen<Self>> = Some(SelfFrozen::new());
+
     fn freeze(self) -> Frozen<Self> {
-        Frozen(self)
+        Frozen::new(self)
     }
 }
 impl<'a, T: Freezable + ?Sized> Unfreezable<&'a T> for &'a T {
+    const SELF_FROZEN: Option<SelfFrozen<&'a T, Self>> = Some(SelfFrozen::new());
+
     fn thaw(wrapped: <&'a T as Freezable>::Frozen) -> Self {
         wrapped
     }
 }
+impl<'a, T: Freezable + ?Sized> CloneUnfreezable<&'a T> for &'a T {
+    fn thaw_cloned(wrapped: &<&'a T as Freezable>::Frozen) -> Self {
+        wrapped
+    }
+}
+impl<T: Freezable + ?Sized> FreezesToSelf for &T {
+}
 
 #[derive(Debug)]
 pub struct FrozenMutRef<'a, T: Freezable + ?Sized>(&'a mut T);
@@ -123,7 +152,7 @@ impl<'a, T: Freezable + ?Sized> Freezable for &'a mut T {
     type Frozen = FrozenMutRef<'a, T>;
 
     fn freeze(self) -> Frozen<Self> {
-        Frozen(FrozenMutRef(self))
+        Frozen::new(FrozenMutRef(self))
     }
 }
 impl<'a, T: Freezable + ?Sized> Unfreezable<&'a mut T> for &'a mut T {
@@ -136,4 +165,3 @@ impl<'a, T: Freezable + ?Sized> Unfreezable<&'a mut T> for &'a T {
         wrapped
     }
 }
-
@@ -0,0 +1,54 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::archive::{
+    validate_map,
+    validate_set,
+    write_map,
+    write_set,
+    Archive,
+    ArchiveError,
+    ArchiveKey,
+    ArchiveWriter,
+    ArchivedFrozenMap,
+    ArchivedFrozenSet,
+};
+use crate::collections::{FrozenMap, FrozenSet};
+
+/// Stored sorted by key, and viewed as an [`ArchivedFrozenMap`].
+impl<K: Hash + Eq + ArchiveKey, V: Archive, S: BuildHasher> Archive
+    for FrozenMap<K, V, S>
+{
+    const SIZE: usize = 16;
+
+    type Archived<'a> = ArchivedFrozenMap<'a, K, V>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        write_map(writer, pos, self.iter());
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        validate_map::<K, V>(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> Self::Archived<'_> {
+        ArchivedFrozenMap::new(buf, pos)
+    }
+}
+/// Stored sorted, and viewed as an [`ArchivedFrozenSet`].
+impl<T: Hash + Eq + ArchiveKey, S: BuildHasher> Archive for FrozenSet<T, S> {
+    const SIZE: usize = 16;
+
+    type Archived<'a> = ArchivedFrozenSet<'a, T>;
+
+    fn write(&self, writer: &mut ArchiveWriter, pos: usize) {
+        write_set(writer, pos, self.iter());
+    }
+
+    fn validate(buf: &[u8], pos: usize) -> Result<(), ArchiveError> {
+        validate_set::<T>(buf, pos)
+    }
+
+    unsafe fn view(buf: &[u8], pos: usize) -> Self::Archived<'_> {
+        ArchivedFrozenSet::new(buf, pos)
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt::{self, Display, Formatter};
+use std::hash::{BuildHasher, Hash};
+
+use crate::canonical::{FmtCanonical, Options};
+use crate::collections::{
+    FrozenBag,
+    FrozenMap,
+    FrozenMultiMap,
+    FrozenSet,
+    PersistentMap,
+};
+#[cfg(feature = "hashbrown")]
+use crate::collections::{FrozenIndexMap, FrozenIndexSet};
+#[cfg(feature = "hashbrown")]
+use crate::frozen_alloc::canonical::{display_map, display_set};
+use crate::frozen_alloc::canonical::{
+    display_map_sorted,
+    display_set_sorted,
+    fmt_map_sorted,
+    fmt_set_sorted,
+};
+
+impl<K: Hash + Eq + Ord + FmtCanonical, V: FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenMap<K, V, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_map_sorted(self.iter(), options, f)
+    }
+}
+impl<T: Hash + Eq + Ord + FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenSet<T, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_set_sorted(self.iter(), options, f)
+    }
+}
+impl<K: Hash + Eq + Ord + FmtCanonical, V: FmtCanonical, S: BuildHasher> FmtCanonical
+    for PersistentMap<K, V, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_map_sorted(self.iter(), options, f)
+    }
+}
+/// Index maps keep their insertion order.
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + FmtCanonical, V: FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenIndexMap<K, V, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.iter()
+                    .map(|(key, value)| (options.nested(key), options.nested(value))),
+            )
+            .finish()
+    }
+}
+/// Index sets keep their insertion order.
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenIndexSet<T, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|item| options.nested(item)))
+            .finish()
+    }
+}
+/// Written as a map from each item to its count, sorted by item.
+impl<T: Hash + Eq + Ord + FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenBag<T, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        let counts: Vec<_> = self.iter().collect();
+        fmt_map_sorted(
+            counts.iter().map(|(item, count)| (*item, count)),
+            options,
+            f,
+        )
+    }
+}
+/// Written as a map from each key to its list of values, sorted by key.
+/// Each list keeps its order.
+impl<K: Hash + Eq + Ord + FmtCanonical, V: FmtCanonical, S: BuildHasher> FmtCanonical
+    for FrozenMultiMap<K, V, S>
+{
+    fn fmt_canonical(&self, options: Options, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt_map_sorted(self.groups(), options, f)
+    }
+}
+
+/// Written as a map literal, sorted by key.
+impl<K: Hash + Eq + Ord + Display, V: Display, S: BuildHasher> Display
+    for FrozenMap<K, V, S>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_map_sorted(self.iter(), f)
+    }
+}
+/// Written as a set literal, in ascending order.
+impl<T: Hash + Eq + Ord + Display, S: BuildHasher> Display for FrozenSet<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_set_sorted(self.iter(), f)
+    }
+}
+/// Written as a map literal, sorted by key.
+impl<K: Hash + Eq + Ord + Display, V: Display, S: BuildHasher> Display
+    for PersistentMap<K, V, S>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_map_sorted(self.iter(), f)
+    }
+}
+/// Written as a map literal, in insertion order.
+#[cfg(feature = "hashbrown")]
+impl<K: Hash + Eq + Display, V: Display, S: BuildHasher> Display
+    for FrozenIndexMap<K, V, S>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_map(self.iter(), f)
+    }
+}
+/// Written as a set literal, in insertion order.
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + Display, S: BuildHasher> Display for FrozenIndexSet<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_set(self.iter(), f)
+    }
+}
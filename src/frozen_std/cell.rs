@@ -1,12 +1,12 @@
 use std::cell::{Cell, RefCell};
 
-use crate::{Freezable, Frozen, Unfreezable};
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
 
 impl<T: Freezable> Freezable for RefCell<T> {
     type Frozen = T::Frozen;
 
     fn freeze(self) -> Frozen<Self> {
-        Frozen(self.into_inner().freeze().0)
+        self.into_inner().freeze().rewrap()
     }
 }
 impl<T: Freezable, U: Unfreezable<T>> Unfreezable<T> for RefCell<U> {
@@ -14,12 +14,17 @@ impl<T: Freezable, U: Unfreezable<T>> Unfreezable<T> for RefCell<U> {
         RefCell::new(U::thaw(wrapped))
     }
 }
+impl<T: Freezable, U: CloneUnfreezable<T>> CloneUnfreezable<T> for RefCell<U> {
+    fn thaw_cloned(wrapped: &<T as Freezable>::Frozen) -> Self {
+        RefCell::new(U::thaw_cloned(wrapped))
+    }
+}
 
 impl<T: Freezable> Freezable for Cell<T> {
     type Frozen = T::Frozen;
 
     fn freeze(self) -> Frozen<Self> {
-        Frozen(self.into_inner().freeze().0)
+        self.into_inner().freeze().rewrap()
     }
 }
 impl<T: Freezable, U: Unfreezable<T>> Unfreezable<T> for Cell<U> {
@@ -27,3 +32,8 @@ impl<T: Freezable, U: Unfreezable<T>> Unfreezable<T> for Cell<U> {
         Cell::new(U::thaw(wrapped))
     }
 }
+impl<T: Freezable, U: CloneUnfreezable<T>> CloneUnfreezable<T> for Cell<U> {
+    fn thaw_cloned(wrapped: &<T as Freezable>::Frozen) -> Self {
+        Cell::new(U::thaw_cloned(wrapped))
+    }
+}
@@ -0,0 +1,276 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, RandomState};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FusedIterator;
+use std::ptr::NonNull;
+
+use crate::collections::hash_unordered;
+use crate::heap_size::{HeapSize, SharedAllocations};
+use crate::verify::StableAddress;
+use crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+/// A count of each of a set of items, which freezes into a [`FrozenBag`].
+///
+/// Items counted zero times are left out of the frozen bag.
+#[derive(Clone, Debug, Default)]
+pub struct Bag<T, S = RandomState>(pub HashMap<T, usize, S>);
+impl<T, S> From<HashMap<T, usize, S>> for Bag<T, S> {
+    fn from(counts: HashMap<T, usize, S>) -> Self {
+        Bag(counts)
+    }
+}
+
+/// A frozen multiset, which holds each of its items some number of times.
+///
+/// Two bags are equal, and hash the same, if they hold the same items the same
+/// number of times, whatever order the items were counted in.
+///
+/// Created by freezing a [`Bag`], or by counting the items of an iterator with
+/// [`freeze_counts`](crate::FreezableIteratorExt::freeze_counts).
+#[derive(Clone)]
+pub struct FrozenBag<T: Hash + Eq, S: BuildHasher = RandomState> {
+    counts: HashMap<T, usize, S>,
+    total: usize,
+}
+impl<T: Hash + Eq, S: BuildHasher> FrozenBag<T, S> {
+    pub(crate) fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    pub(crate) fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        FrozenBag {
+            counts: HashMap::with_capacity_and_hasher(capacity, hasher),
+            total: 0,
+        }
+    }
+
+    /// Add `count` copies of `item`, saturating at `usize::MAX`.
+    pub(crate) fn add(&mut self, item: T, count: usize) {
+        if count > 0 {
+            let stored = self.counts.entry(item).or_insert(0);
+            *stored = stored.saturating_add(count);
+            self.total = self.total.saturating_add(count);
+        }
+    }
+
+    /// The number of times `item` is in the bag, or `usize::MAX` if that is
+    /// too many to count.
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    /// Whether `item` is in the bag at least once.
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.counts.contains_key(item)
+    }
+
+    /// The number of items in the bag, counting every copy, or `usize::MAX`
+    /// if that is too many to count.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The number of distinct items in the bag.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Whether the bag holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The bag's hasher.
+    pub fn hasher(&self) -> &S {
+        self.counts.hasher()
+    }
+
+    /// Iterate over the distinct items and their counts, in arbitrary order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.counts.iter())
+    }
+
+    /// The `n` items with the highest counts, most common first.
+    ///
+    /// Items with equal counts are in arbitrary order.
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<_> = self.iter().collect();
+        items.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        items.truncate(n);
+        items
+    }
+
+    /// The multiset union, which holds each item as many times as the bag
+    /// holding it most.
+    pub fn union(&self, other: &Self) -> Self
+    where
+        T: Clone,
+        S: Clone,
+    {
+        let mut union = self.clone();
+        for (item, count) in other {
+            let own = self.count(item);
+            if count > own {
+                union.add(item.clone(), count - own);
+            }
+        }
+        union
+    }
+
+    /// The multiset intersection, which holds each item as many times as the
+    /// bag holding it least.
+    pub fn intersection(&self, other: &Self) -> Self
+    where
+        T: Clone,
+        S: Clone,
+    {
+        let mut intersection = FrozenBag::with_hasher(self.hasher().clone());
+        for (item, count) in self {
+            intersection.add(item.clone(), count.min(other.count(item)));
+        }
+        intersection
+    }
+}
+impl<T: Hash + Eq + Debug, S: BuildHasher> Debug for FrozenBag<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for FrozenBag<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.counts == other.counts
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> Eq for FrozenBag<T, S> {
+}
+impl<T: Hash + Eq, S: BuildHasher> Hash for FrozenBag<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.counts.iter(), state);
+    }
+}
+/// Empty bags have no address.
+impl<T: Hash + Eq, S: BuildHasher> StableAddress for FrozenBag<T, S> {
+    fn stable_address(&self) -> Option<NonNull<()>> {
+        self.counts
+            .values()
+            .next()
+            .map(|count| NonNull::from(count).cast())
+    }
+}
+impl<'a, T: Hash + Eq, S: BuildHasher> IntoIterator for &'a FrozenBag<T, S> {
+    type IntoIter = Iter<'a, T>;
+    type Item = (&'a T, usize);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> IntoIterator for FrozenBag<T, S> {
+    type IntoIter = hash_map::IntoIter<T, usize>;
+    type Item = (T, usize);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+/// An iterator over the distinct items of a [`FrozenBag`] and their counts.
+#[derive(Clone)]
+pub struct Iter<'a, T>(hash_map::Iter<'a, T, usize>);
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(item, &count)| (item, count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {
+}
+impl<T> FusedIterator for Iter<'_, T> {
+}
+
+/// Freeze a count of each item into a [`FrozenBag`].
+impl<T: Freezable, S: BuildHasher + Clone> Freezable for Bag<T, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Frozen = FrozenBag<Frozen<T>, S>;
+
+    fn freeze(self) -> Frozen<Self> {
+        let Bag(counts) = self;
+        let mut bag =
+            FrozenBag::with_capacity_and_hasher(counts.len(), counts.hasher().clone());
+        for (item, count) in counts {
+            bag.add(item.freeze(), count);
+        }
+        Frozen::new_verified(bag)
+    }
+}
+impl<T: Freezable, RT: Hash + Eq + Unfreezable<T>, S: BuildHasher + Clone>
+    Unfreezable<Bag<T, S>> for HashMap<RT, usize, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <Bag<T, S> as Freezable>::Frozen) -> Self {
+        let mut counts = HashMap::with_capacity_and_hasher(
+            val.counts.len(),
+            val.counts.hasher().clone(),
+        );
+        counts.extend(
+            val.counts
+                .into_iter()
+                .map(|(item, count)| (item.thaw(), count)),
+        );
+        counts
+    }
+}
+impl<T: Freezable, RT: Hash + Eq + CloneUnfreezable<T>, S: BuildHasher + Clone>
+    CloneUnfreezable<Bag<T, S>> for HashMap<RT, usize, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<Bag<T, S> as Freezable>::Frozen) -> Self {
+        let mut counts =
+            HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+        counts.extend(val.iter().map(|(item, count)| (item.to_thawed(), count)));
+        counts
+    }
+}
+impl<T: Freezable, RT: Hash + Eq + Unfreezable<T>, S: BuildHasher + Clone>
+    Unfreezable<Bag<T, S>> for Bag<RT, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <Bag<T, S> as Freezable>::Frozen) -> Self {
+        Bag(<HashMap<RT, usize, S> as Unfreezable<Bag<T, S>>>::thaw(val))
+    }
+}
+impl<T: Freezable, RT: Hash + Eq + CloneUnfreezable<T>, S: BuildHasher + Clone>
+    CloneUnfreezable<Bag<T, S>> for Bag<RT, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<Bag<T, S> as Freezable>::Frozen) -> Self {
+        Bag(<HashMap<RT, usize, S> as CloneUnfreezable<Bag<T, S>>>::thaw_cloned(val))
+    }
+}
+
+impl<T: Hash + Eq + HeapSize, S: BuildHasher> HeapSize for FrozenBag<T, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.counts.heap_size_with(shared)
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+
+use crate::collections::{BitSet, BitSetElement, FrozenBitSet};
+use crate::{CloneUnfreezable, DeepImmutable, Frozen, Unfreezable};
+
+/// Repack a frozen [`HashSet`] into a [`FrozenBitSet`].
+impl<T: BitSetElement + Hash + DeepImmutable, S: BuildHasher + Clone>
+    From<Frozen<HashSet<T, S>>> for Frozen<BitSet<HashSet<T, S>>>
+{
+    fn from(frozen: Frozen<HashSet<T, S>>) -> Self {
+        frozen.verify();
+        Frozen::new(
+            frozen
+                .into_inner()
+                .into_iter()
+                .map(Frozen::into_inner)
+                .collect(),
+        )
+    }
+}
+impl<T: BitSetElement + Hash, C: IntoIterator<Item = T>, S: BuildHasher + Default>
+    Unfreezable<BitSet<C>> for HashSet<T, S>
+{
+    fn thaw(val: FrozenBitSet<T>) -> Self {
+        val.into_iter().collect()
+    }
+}
+impl<T: BitSetElement + Hash, C: IntoIterator<Item = T>, S: BuildHasher + Default>
+    CloneUnfreezable<BitSet<C>> for HashSet<T, S>
+{
+    fn thaw_cloned(val: &FrozenBitSet<T>) -> Self {
+        val.iter().collect()
+    }
+}
@@ -0,0 +1,507 @@
+use std::collections::hash_map::{self, RandomState};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{BuildHasher, Hash};
+
+#[cfg(feature = "hashbrown")]
+use super::index_map::{FrozenIndexMap, IndexedMap};
+#[cfg(feature = "hashbrown")]
+use super::index_set::{FrozenIndexSet, IndexedSet};
+use super::{FrozenMap, FrozenSet};
+use crate::{DeepImmutable, Freezable, Frozen};
+
+/// What a builder does when it is given a key it already holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+    /// Reject the new entry with a [`DuplicateKey`] error.
+    Error,
+    /// Keep the entry which was inserted first.
+    KeepFirst,
+    /// Replace the existing entry with the new one, like [`HashMap::insert`].
+    #[default]
+    KeepLast,
+}
+
+/// The error returned when inserting a duplicate key into a builder using
+/// [`DuplicatePolicy::Error`]. Holds the rejected key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DuplicateKey<K>(pub K);
+impl<K: Debug> Display for DuplicateKey<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate key: {:?}", self.0)
+    }
+}
+impl<K: Debug> Error for DuplicateKey<K> {
+}
+
+/// A builder which freezes each entry as it is inserted, and seals directly
+/// into a frozen [`HashMap`].
+///
+/// Created with [`FrozenMap::builder`] or [`FrozenMap::builder_with_hasher`].
+pub struct FrozenMapBuilder<K: Freezable, V: Freezable, S = RandomState>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    map: HashMap<Frozen<K>, Frozen<V>, S>,
+    policy: DuplicatePolicy,
+}
+impl<K: Freezable, V: Freezable> FrozenMap<Frozen<K>, Frozen<V>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen map.
+    pub fn builder() -> FrozenMapBuilder<K, V> {
+        Self::builder_with_hasher(RandomState::new())
+    }
+}
+impl<K: Freezable, V: Freezable, S: BuildHasher> FrozenMap<Frozen<K>, Frozen<V>, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen map which will use the given hasher.
+    pub fn builder_with_hasher(hasher: S) -> FrozenMapBuilder<K, V, S> {
+        FrozenMapBuilder {
+            map: HashMap::with_hasher(hasher),
+            policy: DuplicatePolicy::default(),
+        }
+    }
+}
+impl<K: Freezable, V: Freezable, S: BuildHasher> FrozenMapBuilder<K, V, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Set what to do with duplicate keys. Defaults to
+    /// [`DuplicatePolicy::KeepLast`].
+    #[must_use]
+    pub fn on_duplicate(mut self, policy: DuplicatePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Reserve space for at least `capacity` entries in total.
+    #[must_use]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.reserve(capacity.saturating_sub(self.map.len()));
+        self
+    }
+
+    /// Reserve space for at least `additional` more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    /// The number of entries inserted so far.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether no entries have been inserted yet.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Freeze and insert an entry.
+    ///
+    /// # Errors
+    ///
+    /// If the key is already present and the builder uses
+    /// [`DuplicatePolicy::Error`], the frozen key is returned and the entry is
+    /// discarded.
+    pub fn insert(&mut self, key: K, value: V) -> Result<(), DuplicateKey<Frozen<K>>> {
+        let key = key.freeze();
+        if self.policy == DuplicatePolicy::Error && self.map.contains_key(&key) {
+            return Err(DuplicateKey(key));
+        }
+        match self.map.entry(key) {
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(value.freeze());
+            }
+            hash_map::Entry::Occupied(mut entry) => {
+                if self.policy == DuplicatePolicy::KeepLast {
+                    entry.insert(value.freeze());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Freeze and insert every entry of `iter`.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first duplicate key if the builder uses
+    /// [`DuplicatePolicy::Error`]. Entries before it are kept.
+    pub fn try_extend<I: IntoIterator<Item = (K, V)>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), DuplicateKey<Frozen<K>>> {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Finish building, without copying the entries.
+    pub fn seal(self) -> Frozen<HashMap<K, V, S>>
+    where
+        S: Clone,
+    {
+        Frozen::new(FrozenMap::from_map(self.map))
+    }
+}
+
+/// A builder which freezes each element as it is inserted, and seals directly
+/// into a frozen [`HashSet`].
+///
+/// Created with [`FrozenSet::builder`] or [`FrozenSet::builder_with_hasher`].
+pub struct FrozenSetBuilder<T: Freezable, S = RandomState>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    set: HashSet<Frozen<T>, S>,
+    policy: DuplicatePolicy,
+}
+impl<T: Freezable> FrozenSet<Frozen<T>>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen set.
+    pub fn builder() -> FrozenSetBuilder<T> {
+        Self::builder_with_hasher(RandomState::new())
+    }
+}
+impl<T: Freezable, S: BuildHasher> FrozenSet<Frozen<T>, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen set which will use the given hasher.
+    pub fn builder_with_hasher(hasher: S) -> FrozenSetBuilder<T, S> {
+        FrozenSetBuilder {
+            set: HashSet::with_hasher(hasher),
+            policy: DuplicatePolicy::default(),
+        }
+    }
+}
+impl<T: Freezable, S: BuildHasher> FrozenSetBuilder<T, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Set what to do with duplicate elements. Defaults to
+    /// [`DuplicatePolicy::KeepLast`].
+    #[must_use]
+    pub fn on_duplicate(mut self, policy: DuplicatePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Reserve space for at least `capacity` elements in total.
+    #[must_use]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.reserve(capacity.saturating_sub(self.set.len()));
+        self
+    }
+
+    /// Reserve space for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.set.reserve(additional);
+    }
+
+    /// The number of elements inserted so far.
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Whether no elements have been inserted yet.
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Freeze and insert an element.
+    ///
+    /// # Errors
+    ///
+    /// If an equal element is already present and the builder uses
+    /// [`DuplicatePolicy::Error`], the frozen element is returned.
+    pub fn insert(&mut self, value: T) -> Result<(), DuplicateKey<Frozen<T>>> {
+        let value = value.freeze();
+        match self.policy {
+            DuplicatePolicy::Error if self.set.contains(&value) => {
+                return Err(DuplicateKey(value));
+            }
+            DuplicatePolicy::Error | DuplicatePolicy::KeepFirst => {
+                self.set.insert(value);
+            }
+            DuplicatePolicy::KeepLast => {
+                self.set.replace(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Freeze and insert every element of `iter`.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first duplicate element if the builder uses
+    /// [`DuplicatePolicy::Error`]. Elements before it are kept.
+    pub fn try_extend<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), DuplicateKey<Frozen<T>>> {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.insert(value)?;
+        }
+        Ok(())
+    }
+
+    /// Finish building, without copying the elements.
+    pub fn seal(self) -> Frozen<HashSet<T, S>>
+    where
+        S: Clone,
+    {
+        Frozen::new_verified(FrozenSet::from_set(self.set))
+    }
+}
+
+/// A builder which freezes each entry as it is inserted, and seals directly
+/// into a [`FrozenIndexMap`] which keeps the insertion order.
+///
+/// Created with [`FrozenIndexMap::builder`] or
+/// [`FrozenIndexMap::builder_with_hasher`].
+#[cfg(feature = "hashbrown")]
+pub struct FrozenIndexMapBuilder<
+    K: Freezable,
+    V: Freezable,
+    S: BuildHasher = RandomState,
+> where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    map: FrozenIndexMap<Frozen<K>, Frozen<V>, S>,
+    policy: DuplicatePolicy,
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Freezable, V: Freezable> FrozenIndexMap<Frozen<K>, Frozen<V>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen index map.
+    pub fn builder() -> FrozenIndexMapBuilder<K, V> {
+        Self::builder_with_hasher(RandomState::new())
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Freezable, V: Freezable, S: BuildHasher> FrozenIndexMap<Frozen<K>, Frozen<V>, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen index map which will use the given
+    /// hasher.
+    pub fn builder_with_hasher(hasher: S) -> FrozenIndexMapBuilder<K, V, S> {
+        FrozenIndexMapBuilder {
+            map: FrozenIndexMap::with_capacity_and_hasher(0, hasher),
+            policy: DuplicatePolicy::default(),
+        }
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: Freezable, V: Freezable, S: BuildHasher> FrozenIndexMapBuilder<K, V, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Set what to do with duplicate keys. Defaults to
+    /// [`DuplicatePolicy::KeepLast`], which replaces the value but keeps the
+    /// key's original position.
+    #[must_use]
+    pub fn on_duplicate(mut self, policy: DuplicatePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Reserve space for at least `capacity` entries in total.
+    #[must_use]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.reserve(capacity.saturating_sub(self.map.len()));
+        self
+    }
+
+    /// Reserve space for at least `additional` more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    /// The number of entries inserted so far.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether no entries have been inserted yet.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Freeze and append an entry.
+    ///
+    /// # Errors
+    ///
+    /// If the key is already present and the builder uses
+    /// [`DuplicatePolicy::Error`], the frozen key is returned and the entry is
+    /// discarded.
+    pub fn insert(&mut self, key: K, value: V) -> Result<(), DuplicateKey<Frozen<K>>> {
+        let key = key.freeze();
+        if self.policy != DuplicatePolicy::KeepLast && self.map.contains_key(&key) {
+            return match self.policy {
+                DuplicatePolicy::Error => Err(DuplicateKey(key)),
+                _ => Ok(()),
+            };
+        }
+        self.map.insert_full(key, value.freeze());
+        Ok(())
+    }
+
+    /// Freeze and append every entry of `iter`.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first duplicate key if the builder uses
+    /// [`DuplicatePolicy::Error`]. Entries before it are kept.
+    pub fn try_extend<I: IntoIterator<Item = (K, V)>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), DuplicateKey<Frozen<K>>> {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Finish building, without copying the entries.
+    pub fn seal(self) -> Frozen<IndexedMap<K, V, S>> {
+        Frozen::new(self.map)
+    }
+}
+
+/// A builder which freezes each element as it is inserted, and seals directly
+/// into a [`FrozenIndexSet`] which keeps the insertion order.
+///
+/// Created with [`FrozenIndexSet::builder`] or
+/// [`FrozenIndexSet::builder_with_hasher`].
+#[cfg(feature = "hashbrown")]
+pub struct FrozenIndexSetBuilder<T: Freezable, S: BuildHasher = RandomState>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    set: FrozenIndexMap<Frozen<T>, (), S>,
+    policy: DuplicatePolicy,
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Freezable> FrozenIndexSet<Frozen<T>>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen index set.
+    pub fn builder() -> FrozenIndexSetBuilder<T> {
+        Self::builder_with_hasher(RandomState::new())
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Freezable, S: BuildHasher> FrozenIndexSet<Frozen<T>, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Create a builder for a frozen index set which will use the given
+    /// hasher.
+    pub fn builder_with_hasher(hasher: S) -> FrozenIndexSetBuilder<T, S> {
+        FrozenIndexSetBuilder {
+            set: FrozenIndexMap::with_capacity_and_hasher(0, hasher),
+            policy: DuplicatePolicy::default(),
+        }
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Freezable, S: BuildHasher> FrozenIndexSetBuilder<T, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Set what to do with duplicate elements. Defaults to
+    /// [`DuplicatePolicy::KeepLast`], which replaces the element but keeps its
+    /// original position.
+    #[must_use]
+    pub fn on_duplicate(mut self, policy: DuplicatePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Reserve space for at least `capacity` elements in total.
+    #[must_use]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.reserve(capacity.saturating_sub(self.set.len()));
+        self
+    }
+
+    /// Reserve space for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.set.reserve(additional);
+    }
+
+    /// The number of elements inserted so far.
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Whether no elements have been inserted yet.
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Freeze and append an element.
+    ///
+    /// # Errors
+    ///
+    /// If an equal element is already present and the builder uses
+    /// [`DuplicatePolicy::Error`], the frozen element is returned.
+    pub fn insert(&mut self, value: T) -> Result<(), DuplicateKey<Frozen<T>>> {
+        let value = value.freeze();
+        match self.policy {
+            DuplicatePolicy::Error if self.set.contains_key(&value) => {
+                return Err(DuplicateKey(value));
+            }
+            DuplicatePolicy::Error | DuplicatePolicy::KeepFirst => {
+                self.set.insert_full(value, ());
+            }
+            DuplicatePolicy::KeepLast => {
+                self.set.replace_full(value, ());
+            }
+        }
+        Ok(())
+    }
+
+    /// Freeze and append every element of `iter`.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first duplicate element if the builder uses
+    /// [`DuplicatePolicy::Error`]. Elements before it are kept.
+    pub fn try_extend<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), DuplicateKey<Frozen<T>>> {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.insert(value)?;
+        }
+        Ok(())
+    }
+
+    /// Finish building, without copying the elements.
+    pub fn seal(self) -> Frozen<IndexedSet<T, S>> {
+        Frozen::new_verified(FrozenIndexSet(self.set))
+    }
+}
@@ -0,0 +1,412 @@
+use core::borrow::Borrow;
+use core::ops::Index;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FusedIterator;
+use std::ptr::NonNull;
+use std::{mem, slice, vec};
+
+use ::hashbrown::HashTable;
+
+use crate::heap_size::{HeapSize, SharedAllocations};
+use crate::verify::StableAddress;
+use crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+/// A list of entries which freezes into a [`FrozenIndexMap`], keeping their
+/// order, along with the hasher the frozen map will use.
+///
+/// If a key appears more than once, the frozen map keeps its first position
+/// and its last value, like [`HashMap::extend`].
+#[derive(Clone, Debug)]
+pub struct IndexedMap<K, V, S = RandomState>(pub Vec<(K, V)>, pub S);
+impl<K, V> IndexedMap<K, V> {
+    /// Wrap `entries`, to be frozen with a new [`RandomState`].
+    pub fn new(entries: Vec<(K, V)>) -> Self {
+        IndexedMap(entries, RandomState::new())
+    }
+}
+impl<K, V> From<Vec<(K, V)>> for IndexedMap<K, V> {
+    fn from(entries: Vec<(K, V)>) -> Self {
+        Self::new(entries)
+    }
+}
+
+/// A frozen insertion-ordered map, which supports both hash lookups and
+/// positional access.
+///
+/// Iteration follows insertion order, and two maps are only equal (and only
+/// hash the same) if they hold the same entries in the same order.
+///
+/// Created by freezing an [`IndexedMap`], or with [`FrozenIndexMap::builder`].
+#[derive(Clone)]
+pub struct FrozenIndexMap<K: Hash + Eq, V, S: BuildHasher = RandomState> {
+    entries: Vec<(K, V)>,
+    indices: HashTable<usize>,
+    hasher: S,
+}
+impl<K: Hash + Eq, V, S: BuildHasher> FrozenIndexMap<K, V, S> {
+    pub(crate) fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        FrozenIndexMap {
+            entries: Vec::with_capacity(capacity),
+            indices: HashTable::with_capacity(capacity),
+            hasher,
+        }
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        let (entries, hasher) = (&self.entries, &self.hasher);
+        self.indices
+            .reserve(additional, |&i| hasher.hash_one(&entries[i].0));
+        self.entries.reserve(additional);
+    }
+
+    /// Insert an entry at the end, or replace the value of an equal key in
+    /// place. Returns the entry's index and the replaced value.
+    pub(crate) fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        match self.get_index_of(&key) {
+            Some(index) => {
+                (index, Some(mem::replace(&mut self.entries[index].1, value)))
+            }
+            None => (self.push(key, value), None),
+        }
+    }
+
+    /// Like [`insert_full`](Self::insert_full), but also replaces the key of
+    /// an existing entry.
+    pub(crate) fn replace_full(&mut self, key: K, value: V) -> (usize, Option<(K, V)>) {
+        match self.get_index_of(&key) {
+            Some(index) => (
+                index,
+                Some(mem::replace(&mut self.entries[index], (key, value))),
+            ),
+            None => (self.push(key, value), None),
+        }
+    }
+
+    /// Take apart the map, discarding its index table.
+    pub(crate) fn into_parts(self) -> (Vec<(K, V)>, S) {
+        (self.entries, self.hasher)
+    }
+
+    /// Append an entry whose key is not yet present.
+    fn push(&mut self, key: K, value: V) -> usize {
+        let index = self.entries.len();
+        let (entries, hasher) = (&self.entries, &self.hasher);
+        self.indices
+            .insert_unique(hasher.hash_one(&key), index, |&i| {
+                hasher.hash_one(&entries[i].0)
+            });
+        self.entries.push((key, value));
+        index
+    }
+
+    /// The number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The map's hasher.
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// The index of the entry for `key`.
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = self.hasher.hash_one(key);
+        self.indices
+            .find(hash, |&i| self.entries[i].0.borrow() == key)
+            .copied()
+    }
+
+    /// The index, key and value of the entry for `key`.
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.get_index_of(key)?;
+        let (key, value) = &self.entries[index];
+        Some((index, key, value))
+    }
+
+    /// The value for `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_full(key).map(|(_, _, value)| value)
+    }
+
+    /// The stored key and the value for `key`.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_full(key).map(|(_, key, value)| (key, value))
+    }
+
+    /// Whether the map has an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    /// The entry at `index`, in insertion order.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+
+    /// The first entry inserted.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// The last entry inserted.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.len()
+            .checked_sub(1)
+            .and_then(|index| self.get_index(index))
+    }
+
+    /// The entries, in insertion order.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.entries
+    }
+
+    /// Iterate over the entries in insertion order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter(self.entries.iter())
+    }
+
+    /// Iterate over the keys in insertion order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator + '_ {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over the values in insertion order.
+    pub fn values(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator + '_ {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+impl<K: Hash + Eq + Debug, V: Debug, S: BuildHasher> Debug for FrozenIndexMap<K, V, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for FrozenIndexMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for FrozenIndexMap<K, V, S> {
+}
+impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for FrozenIndexMap<K, V, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entries.hash(state);
+    }
+}
+/// Empty maps have no address.
+impl<K: Hash + Eq, V, S: BuildHasher> StableAddress for FrozenIndexMap<K, V, S> {
+    fn stable_address(&self) -> Option<NonNull<()>> {
+        self.entries.stable_address()
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher, B: Freezable> Index<B> for FrozenIndexMap<K, V, S>
+where
+    K: Borrow<Frozen<B>>,
+    B::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Output = V;
+
+    fn index(&self, index: B) -> &Self::Output {
+        self.get(&index.freeze())
+            .expect("key not found in FrozenIndexMap")
+    }
+}
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a FrozenIndexMap<K, V, S> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher> IntoIterator for FrozenIndexMap<K, V, S> {
+    type IntoIter = vec::IntoIter<(K, V)>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// An iterator over the entries of a [`FrozenIndexMap`], in insertion order.
+#[derive(Clone)]
+pub struct Iter<'a, K, V>(slice::Iter<'a, (K, V)>);
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+}
+impl<K, V> FusedIterator for Iter<'_, K, V> {
+}
+
+/// Freeze a list of entries into a [`FrozenIndexMap`].
+impl<K: Freezable, V: Freezable, S: BuildHasher> Freezable for IndexedMap<K, V, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Frozen = FrozenIndexMap<Frozen<K>, Frozen<V>, S>;
+
+    fn freeze(self) -> Frozen<Self> {
+        let IndexedMap(entries, hasher) = self;
+        let mut map = FrozenIndexMap::with_capacity_and_hasher(entries.len(), hasher);
+        for (key, value) in entries {
+            map.insert_full(key.freeze(), value.freeze());
+        }
+        Frozen::new(map)
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher,
+    > Unfreezable<IndexedMap<K, V, S>> for IndexedMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <IndexedMap<K, V, S> as Freezable>::Frozen) -> Self {
+        let (entries, hasher) = val.into_parts();
+        IndexedMap(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.thaw(), v.thaw()))
+                .collect(),
+            hasher,
+        )
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: CloneUnfreezable<K>,
+        RV: CloneUnfreezable<V>,
+        S: BuildHasher + Clone,
+    > CloneUnfreezable<IndexedMap<K, V, S>> for IndexedMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<IndexedMap<K, V, S> as Freezable>::Frozen) -> Self {
+        IndexedMap(
+            val.iter()
+                .map(|(k, v)| (k.to_thawed(), v.to_thawed()))
+                .collect(),
+            val.hasher().clone(),
+        )
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher,
+    > Unfreezable<IndexedMap<K, V, S>> for Vec<(RK, RV)>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <IndexedMap<K, V, S> as Freezable>::Frozen) -> Self {
+        val.into_iter().map(|(k, v)| (k.thaw(), v.thaw())).collect()
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: CloneUnfreezable<K>,
+        RV: CloneUnfreezable<V>,
+        S: BuildHasher,
+    > CloneUnfreezable<IndexedMap<K, V, S>> for Vec<(RK, RV)>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<IndexedMap<K, V, S> as Freezable>::Frozen) -> Self {
+        val.iter()
+            .map(|(k, v)| (k.to_thawed(), v.to_thawed()))
+            .collect()
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher,
+    > Unfreezable<IndexedMap<K, V, S>> for HashMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <IndexedMap<K, V, S> as Freezable>::Frozen) -> Self {
+        let (entries, hasher) = val.into_parts();
+        let mut map = HashMap::with_capacity_and_hasher(entries.len(), hasher);
+        map.extend(entries.into_iter().map(|(k, v)| (k.thaw(), v.thaw())));
+        map
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + CloneUnfreezable<K>,
+        RV: CloneUnfreezable<V>,
+        S: BuildHasher + Clone,
+    > CloneUnfreezable<IndexedMap<K, V, S>> for HashMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<IndexedMap<K, V, S> as Freezable>::Frozen) -> Self {
+        let mut map =
+            HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+        map.extend(val.iter().map(|(k, v)| (k.to_thawed(), v.to_thawed())));
+        map
+    }
+}
+
+impl<K: Hash + Eq + HeapSize, V: HeapSize, S: BuildHasher> HeapSize
+    for FrozenIndexMap<K, V, S>
+{
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.entries.heap_size_with(shared) + self.indices.allocation_size()
+    }
+}
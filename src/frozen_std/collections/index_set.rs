@@ -0,0 +1,290 @@
+use core::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FusedIterator;
+use std::ptr::NonNull;
+use std::vec;
+
+use super::index_map::{self, FrozenIndexMap};
+use crate::verify::StableAddress;
+use crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+/// A list of elements which freezes into a [`FrozenIndexSet`], keeping their
+/// order, along with the hasher the frozen set will use.
+///
+/// If an element appears more than once, the frozen set keeps the first.
+#[derive(Clone, Debug)]
+pub struct IndexedSet<T, S = RandomState>(pub Vec<T>, pub S);
+impl<T> IndexedSet<T> {
+    /// Wrap `elements`, to be frozen with a new [`RandomState`].
+    pub fn new(elements: Vec<T>) -> Self {
+        IndexedSet(elements, RandomState::new())
+    }
+}
+impl<T> From<Vec<T>> for IndexedSet<T> {
+    fn from(elements: Vec<T>) -> Self {
+        Self::new(elements)
+    }
+}
+
+/// A frozen insertion-ordered set, which supports both hash lookups and
+/// positional access.
+///
+/// Iteration follows insertion order, and two sets are only equal (and only
+/// hash the same) if they hold the same elements in the same order.
+///
+/// Created by freezing an [`IndexedSet`], or with [`FrozenIndexSet::builder`].
+#[derive(Clone)]
+pub struct FrozenIndexSet<T: Hash + Eq, S: BuildHasher = RandomState>(
+    pub(crate) FrozenIndexMap<T, (), S>,
+);
+impl<T: Hash + Eq, S: BuildHasher> FrozenIndexSet<T, S> {
+    /// The number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The set's hasher.
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+
+    /// The index of the element equal to `value`.
+    pub fn get_index_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_index_of(value)
+    }
+
+    /// The index of the element equal to `value`, and the element itself.
+    pub fn get_full<Q>(&self, value: &Q) -> Option<(usize, &T)>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0
+            .get_full(value)
+            .map(|(index, value, ())| (index, value))
+    }
+
+    /// The element equal to `value`.
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_full(value).map(|(_, value)| value)
+    }
+
+    /// Whether the set has an element equal to `value`.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.contains_key(value)
+    }
+
+    /// The element at `index`, in insertion order.
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.0.get_index(index).map(|(value, ())| value)
+    }
+
+    /// The first element inserted.
+    pub fn first(&self) -> Option<&T> {
+        self.get_index(0)
+    }
+
+    /// The last element inserted.
+    pub fn last(&self) -> Option<&T> {
+        self.0.last().map(|(value, ())| value)
+    }
+
+    /// Iterate over the elements in insertion order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.iter())
+    }
+}
+impl<T: Hash + Eq + Debug, S: BuildHasher> Debug for FrozenIndexSet<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for FrozenIndexSet<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> Eq for FrozenIndexSet<T, S> {
+}
+impl<T: Hash + Eq, S: BuildHasher> Hash for FrozenIndexSet<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+/// Empty sets have no address.
+impl<T: Hash + Eq, S: BuildHasher> StableAddress for FrozenIndexSet<T, S> {
+    fn stable_address(&self) -> Option<NonNull<()>> {
+        self.0.stable_address()
+    }
+}
+impl<'a, T: Hash + Eq, S: BuildHasher> IntoIterator for &'a FrozenIndexSet<T, S> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<T: Hash + Eq, S: BuildHasher> IntoIterator for FrozenIndexSet<T, S> {
+    type IntoIter = IntoIter<T>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+/// An iterator over the elements of a [`FrozenIndexSet`], in insertion order.
+#[derive(Clone)]
+pub struct Iter<'a, T>(index_map::Iter<'a, T, ()>);
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(value, ())| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(value, ())| value)
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T> {
+}
+impl<T> FusedIterator for Iter<'_, T> {
+}
+
+/// An owning iterator over the elements of a [`FrozenIndexSet`], in insertion
+/// order.
+pub struct IntoIter<T>(vec::IntoIter<(T, ())>);
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(value, ())| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(value, ())| value)
+    }
+}
+impl<T> ExactSizeIterator for IntoIter<T> {
+}
+impl<T> FusedIterator for IntoIter<T> {
+}
+
+/// Freeze a list of elements into a [`FrozenIndexSet`].
+impl<T: Freezable, S: BuildHasher> Freezable for IndexedSet<T, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Frozen = FrozenIndexSet<Frozen<T>, S>;
+
+    fn freeze(self) -> Frozen<Self> {
+        let IndexedSet(elements, hasher) = self;
+        let mut set = FrozenIndexMap::with_capacity_and_hasher(elements.len(), hasher);
+        for value in elements {
+            set.insert_full(value.freeze(), ());
+        }
+        Frozen::new_verified(FrozenIndexSet(set))
+    }
+}
+impl<T: Freezable, RT: Unfreezable<T>, S: BuildHasher> Unfreezable<IndexedSet<T, S>>
+    for IndexedSet<RT, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(FrozenIndexSet(val): <IndexedSet<T, S> as Freezable>::Frozen) -> Self {
+        let (entries, hasher) = val.into_parts();
+        IndexedSet(
+            entries
+                .into_iter()
+                .map(|(value, ())| value.thaw())
+                .collect(),
+            hasher,
+        )
+    }
+}
+impl<T: Freezable, RT: CloneUnfreezable<T>, S: BuildHasher + Clone>
+    CloneUnfreezable<IndexedSet<T, S>> for IndexedSet<RT, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<IndexedSet<T, S> as Freezable>::Frozen) -> Self {
+        IndexedSet(
+            val.iter().map(Frozen::to_thawed).collect(),
+            val.hasher().clone(),
+        )
+    }
+}
+impl<T: Freezable, RT: Unfreezable<T>, S: BuildHasher> Unfreezable<IndexedSet<T, S>>
+    for Vec<RT>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <IndexedSet<T, S> as Freezable>::Frozen) -> Self {
+        val.into_iter().map(Frozen::thaw).collect()
+    }
+}
+impl<T: Freezable, RT: CloneUnfreezable<T>, S: BuildHasher>
+    CloneUnfreezable<IndexedSet<T, S>> for Vec<RT>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<IndexedSet<T, S> as Freezable>::Frozen) -> Self {
+        val.iter().map(Frozen::to_thawed).collect()
+    }
+}
+impl<T: Freezable, RT: Hash + Eq + Unfreezable<T>, S: BuildHasher>
+    Unfreezable<IndexedSet<T, S>> for HashSet<RT, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(FrozenIndexSet(val): <IndexedSet<T, S> as Freezable>::Frozen) -> Self {
+        let (entries, hasher) = val.into_parts();
+        let mut set = HashSet::with_capacity_and_hasher(entries.len(), hasher);
+        set.extend(entries.into_iter().map(|(value, ())| value.thaw()));
+        set
+    }
+}
+impl<T: Freezable, RT: Hash + Eq + CloneUnfreezable<T>, S: BuildHasher + Clone>
+    CloneUnfreezable<IndexedSet<T, S>> for HashSet<RT, S>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<IndexedSet<T, S> as Freezable>::Frozen) -> Self {
+        let mut set =
+            HashSet::with_capacity_and_hasher(val.len(), val.hasher().clone());
+        set.extend(val.iter().map(Frozen::to_thawed));
+        set
+    }
+}
@@ -1,5 +1,24 @@
+mod bag;
+mod bit_set;
+mod builder;
 mod hash_map;
 mod hash_set;
-mod ordered;
+#[cfg(feature = "hashbrown")]
+mod index_map;
+#[cfg(feature = "hashbrown")]
+mod index_set;
+mod multi_map;
+mod persistent;
+
+pub use bag::{Bag, FrozenBag};
+pub use builder::{DuplicateKey, DuplicatePolicy, FrozenMapBuilder, FrozenSetBuilder};
+#[cfg(feature = "hashbrown")]
+pub use builder::{FrozenIndexMapBuilder, FrozenIndexSetBuilder};
 pub use hash_map::FrozenMap;
 pub use hash_set::FrozenSet;
+#[cfg(feature = "hashbrown")]
+pub use index_map::{FrozenIndexMap, IndexedMap};
+#[cfg(feature = "hashbrown")]
+pub use index_set::{FrozenIndexSet, IndexedSet};
+pub use multi_map::{FrozenMultiMap, MultiMap};
+pub use persistent::PersistentMap;
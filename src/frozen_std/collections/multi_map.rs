@@ -0,0 +1,293 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::{self, Entry, RandomState};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FusedIterator;
+use std::slice;
+
+use crate::collections::{freeze_vec, hash_unordered, thaw_vec};
+use crate::heap_size::{HeapSize, SharedAllocations};
+use crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+/// A group of values for each of a set of keys, which freezes into a
+/// [`FrozenMultiMap`].
+///
+/// Keys with no values are left out of the frozen map.
+#[derive(Clone, Debug, Default)]
+pub struct MultiMap<K, V, S = RandomState>(pub HashMap<K, Vec<V>, S>);
+impl<K, V, S> From<HashMap<K, Vec<V>, S>> for MultiMap<K, V, S> {
+    fn from(groups: HashMap<K, Vec<V>, S>) -> Self {
+        MultiMap(groups)
+    }
+}
+
+/// A frozen map from each key to a group of one or more values.
+///
+/// Each group keeps its values in the order they were added. Two maps are
+/// equal, and hash the same, if they hold the same groups, whatever order the
+/// keys were added in.
+///
+/// Created by freezing a [`MultiMap`], or by grouping the items of an iterator
+/// with [`freeze_grouped_by`](crate::FreezableIteratorExt::freeze_grouped_by).
+#[derive(Clone)]
+pub struct FrozenMultiMap<K: Hash + Eq, V, S: BuildHasher = RandomState> {
+    groups: HashMap<K, Vec<V>, S>,
+    len: usize,
+}
+impl<K: Hash + Eq, V, S: BuildHasher> FrozenMultiMap<K, V, S> {
+    pub(crate) fn with_hasher(hasher: S) -> Self {
+        FrozenMultiMap {
+            groups: HashMap::with_hasher(hasher),
+            len: 0,
+        }
+    }
+
+    /// Add `values` to the end of the group for `key`.
+    pub(crate) fn extend_group(&mut self, key: K, mut values: Vec<V>) {
+        if values.is_empty() {
+            return;
+        }
+        self.len += values.len();
+        match self.groups.entry(key) {
+            Entry::Occupied(mut group) => group.get_mut().append(&mut values),
+            Entry::Vacant(group) => {
+                group.insert(values);
+            }
+        }
+    }
+
+    /// Add `value` to the end of the group for `key`.
+    pub(crate) fn push(&mut self, key: K, value: V) {
+        self.groups.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+
+    /// The values for `key`, which are empty if the key is not in the map.
+    pub fn get_all<Q>(&self, key: &Q) -> &[V]
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.groups.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// The number of values for `key`.
+    pub fn len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_all(key).len()
+    }
+
+    /// Whether the map has any values for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.groups.contains_key(key)
+    }
+
+    /// The number of values in the map, across every key.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The number of distinct keys in the map.
+    pub fn key_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Whether the map holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// The map's hasher.
+    pub fn hasher(&self) -> &S {
+        self.groups.hasher()
+    }
+
+    /// Iterate over the distinct keys, in arbitrary order.
+    pub fn keys(&self) -> hash_map::Keys<'_, K, Vec<V>> {
+        self.groups.keys()
+    }
+
+    /// Iterate over each key with its group of values, with the keys in
+    /// arbitrary order.
+    pub fn groups(&self) -> impl ExactSizeIterator<Item = (&K, &[V])> + '_ {
+        self.groups
+            .iter()
+            .map(|(key, values)| (key, values.as_slice()))
+    }
+
+    /// Iterate over every value along with its key, visiting the groups in
+    /// arbitrary order and the values of each group in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            groups: self.groups.iter(),
+            group: None,
+            remaining: self.len,
+        }
+    }
+}
+impl<K: Hash + Eq + Debug, V: Debug, S: BuildHasher> Debug for FrozenMultiMap<K, V, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.groups()).finish()
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for FrozenMultiMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.groups == other.groups
+    }
+}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for FrozenMultiMap<K, V, S> {
+}
+impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for FrozenMultiMap<K, V, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.groups.iter(), state);
+    }
+}
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a FrozenMultiMap<K, V, S> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over every value of a [`FrozenMultiMap`] along with its key.
+#[derive(Clone)]
+pub struct Iter<'a, K, V> {
+    groups: hash_map::Iter<'a, K, Vec<V>>,
+    group: Option<(&'a K, slice::Iter<'a, V>)>,
+    remaining: usize,
+}
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.group {
+                if let Some(value) = values.next() {
+                    self.remaining -= 1;
+                    return Some((key, value));
+                }
+            }
+            let (key, values) = self.groups.next()?;
+            self.group = Some((key, values.iter()));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+}
+impl<K, V> FusedIterator for Iter<'_, K, V> {
+}
+
+/// Freeze the groups of a [`MultiMap`] into a [`FrozenMultiMap`].
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone> Freezable for MultiMap<K, V, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Frozen = FrozenMultiMap<Frozen<K>, Frozen<V>, S>;
+
+    fn freeze(self) -> Frozen<Self> {
+        let MultiMap(groups) = self;
+        let mut map = FrozenMultiMap::with_hasher(groups.hasher().clone());
+        map.groups.reserve(groups.len());
+        for (key, values) in groups {
+            map.extend_group(key.freeze(), freeze_vec(values));
+        }
+        Frozen::new(map)
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher + Clone,
+    > Unfreezable<MultiMap<K, V, S>> for HashMap<RK, Vec<RV>, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <MultiMap<K, V, S> as Freezable>::Frozen) -> Self {
+        let mut groups =
+            HashMap::with_capacity_and_hasher(val.key_count(), val.hasher().clone());
+        groups.extend(
+            val.groups
+                .into_iter()
+                .map(|(key, values)| (key.thaw(), thaw_vec(values))),
+        );
+        groups
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + CloneUnfreezable<K>,
+        RV: CloneUnfreezable<V>,
+        S: BuildHasher + Clone,
+    > CloneUnfreezable<MultiMap<K, V, S>> for HashMap<RK, Vec<RV>, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<MultiMap<K, V, S> as Freezable>::Frozen) -> Self {
+        let mut groups =
+            HashMap::with_capacity_and_hasher(val.key_count(), val.hasher().clone());
+        groups.extend(val.groups().map(|(key, values)| {
+            (
+                key.to_thawed(),
+                values.iter().map(Frozen::to_thawed).collect(),
+            )
+        }));
+        groups
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher + Clone,
+    > Unfreezable<MultiMap<K, V, S>> for MultiMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw(val: <MultiMap<K, V, S> as Freezable>::Frozen) -> Self {
+        MultiMap(<HashMap<RK, Vec<RV>, S> as Unfreezable<
+            MultiMap<K, V, S>,
+        >>::thaw(val))
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + CloneUnfreezable<K>,
+        RV: CloneUnfreezable<V>,
+        S: BuildHasher + Clone,
+    > CloneUnfreezable<MultiMap<K, V, S>> for MultiMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<MultiMap<K, V, S> as Freezable>::Frozen) -> Self {
+        MultiMap(<HashMap<RK, Vec<RV>, S> as CloneUnfreezable<
+            MultiMap<K, V, S>,
+        >>::thaw_cloned(val))
+    }
+}
+
+impl<K: Hash + Eq + HeapSize, V: HeapSize, S: BuildHasher> HeapSize
+    for FrozenMultiMap<K, V, S>
+{
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.groups.heap_size_with(shared)
+    }
+}
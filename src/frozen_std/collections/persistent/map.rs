@@ -0,0 +1,827 @@
+use core::borrow::Borrow;
+use core::fmt::{self, Debug, Formatter};
+use core::iter::FusedIterator;
+use core::ops::Index;
+use core::slice;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::vec;
+
+use crate::collections::{hash_unordered, Persistent};
+use crate::heap_size::{HeapSize, SharedAllocations};
+use crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
+
+/// The number of hash bits consumed by each level of the trie.
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+/// The bit representing `hash` in the bitmap of a node at `shift`.
+fn index_bit(hash: u64, shift: u32) -> u32 {
+    1 << ((hash >> shift) & MASK)
+}
+
+/// A node of the hash array mapped trie.
+///
+/// Only the slots present in `bitmap` are stored, in slot order.
+struct Node<K, V> {
+    bitmap: u32,
+    entries: Vec<Entry<K, V>>,
+}
+enum Entry<K, V> {
+    Leaf(u64, Arc<(K, V)>),
+    /// Several entries whose keys have the same full hash.
+    Collision(u64, Arc<Vec<Arc<(K, V)>>>),
+    Branch(Arc<Node<K, V>>),
+}
+impl<K, V> Clone for Node<K, V> {
+    fn clone(&self) -> Self {
+        Node {
+            bitmap: self.bitmap,
+            entries: self.entries.clone(),
+        }
+    }
+}
+impl<K, V> Clone for Entry<K, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Entry::Leaf(hash, kv) => Entry::Leaf(*hash, kv.clone()),
+            Entry::Collision(hash, kvs) => Entry::Collision(*hash, kvs.clone()),
+            Entry::Branch(node) => Entry::Branch(node.clone()),
+        }
+    }
+}
+impl<K, V> Node<K, V> {
+    fn new() -> Self {
+        Node {
+            bitmap: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// The index into `entries` of the slot represented by `bit`.
+    fn position(&self, bit: u32) -> usize {
+        (self.bitmap & (bit - 1)).count_ones() as usize
+    }
+
+    /// Build a node at `shift` holding two entries with different hashes.
+    fn pair(
+        shift: u32,
+        first_hash: u64,
+        first: Entry<K, V>,
+        hash: u64,
+        entry: Entry<K, V>,
+    ) -> Self {
+        let first_bit = index_bit(first_hash, shift);
+        let bit = index_bit(hash, shift);
+        if first_bit == bit {
+            Node {
+                bitmap: bit,
+                entries: vec![Entry::Branch(Arc::new(Node::pair(
+                    shift + BITS,
+                    first_hash,
+                    first,
+                    hash,
+                    entry,
+                )))],
+            }
+        } else if first_bit < bit {
+            Node {
+                bitmap: first_bit | bit,
+                entries: vec![first, entry],
+            }
+        } else {
+            Node {
+                bitmap: first_bit | bit,
+                entries: vec![entry, first],
+            }
+        }
+    }
+}
+impl<K: Eq, V> Node<K, V> {
+    fn get<Q>(&self, hash: u64, key: &Q) -> Option<&(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mut node = self;
+        let mut shift = 0;
+        loop {
+            let bit = index_bit(hash, shift);
+            if node.bitmap & bit == 0 {
+                return None;
+            }
+            match &node.entries[node.position(bit)] {
+                Entry::Leaf(leaf_hash, kv) => {
+                    return (*leaf_hash == hash && kv.0.borrow() == key)
+                        .then_some(&**kv);
+                }
+                Entry::Collision(collision_hash, kvs) => {
+                    if *collision_hash != hash {
+                        return None;
+                    }
+                    return kvs.iter().map(|kv| &**kv).find(|kv| kv.0.borrow() == key);
+                }
+                Entry::Branch(child) => {
+                    node = child;
+                    shift += BITS;
+                }
+            }
+        }
+    }
+
+    /// Insert an entry into this node, returning whether its key was new.
+    fn insert(&mut self, shift: u32, hash: u64, kv: Arc<(K, V)>) -> bool {
+        let bit = index_bit(hash, shift);
+        let pos = self.position(bit);
+        if self.bitmap & bit == 0 {
+            self.bitmap |= bit;
+            self.entries.insert(pos, Entry::Leaf(hash, kv));
+            return true;
+        }
+        let existing_hash = match &mut self.entries[pos] {
+            Entry::Branch(child) => {
+                return Arc::make_mut(child).insert(shift + BITS, hash, kv);
+            }
+            Entry::Leaf(leaf_hash, old) if *leaf_hash == hash => {
+                if old.0 == kv.0 {
+                    *old = kv;
+                    return false;
+                }
+                let old = old.clone();
+                self.entries[pos] = Entry::Collision(hash, Arc::new(vec![old, kv]));
+                return true;
+            }
+            Entry::Collision(collision_hash, kvs) if *collision_hash == hash => {
+                let kvs = Arc::make_mut(kvs);
+                if let Some(old) = kvs.iter_mut().find(|old| old.0 == kv.0) {
+                    *old = kv;
+                    return false;
+                }
+                kvs.push(kv);
+                return true;
+            }
+            Entry::Leaf(existing_hash, _) | Entry::Collision(existing_hash, _) => {
+                *existing_hash
+            }
+        };
+        let existing = self.entries[pos].clone();
+        self.entries[pos] = Entry::Branch(Arc::new(Node::pair(
+            shift + BITS,
+            existing_hash,
+            existing,
+            hash,
+            Entry::Leaf(hash, kv),
+        )));
+        true
+    }
+
+    /// Remove an entry from this node, returning whether it was present.
+    ///
+    /// Branches left holding a single leaf are collapsed into that leaf, so
+    /// the shape of the trie only depends on its contents.
+    fn remove<Q>(&mut self, shift: u32, hash: u64, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let bit = index_bit(hash, shift);
+        if self.bitmap & bit == 0 {
+            return false;
+        }
+        let pos = self.position(bit);
+        match &mut self.entries[pos] {
+            Entry::Leaf(leaf_hash, kv) => {
+                if *leaf_hash != hash || kv.0.borrow() != key {
+                    return false;
+                }
+                self.bitmap &= !bit;
+                self.entries.remove(pos);
+            }
+            Entry::Collision(collision_hash, kvs) => {
+                if *collision_hash != hash {
+                    return false;
+                }
+                let Some(index) = kvs.iter().position(|kv| kv.0.borrow() == key) else {
+                    return false;
+                };
+                if kvs.len() == 2 {
+                    let remaining = kvs[1 - index].clone();
+                    self.entries[pos] = Entry::Leaf(hash, remaining);
+                } else {
+                    Arc::make_mut(kvs).remove(index);
+                }
+            }
+            Entry::Branch(child) => {
+                let child = Arc::make_mut(child);
+                if !child.remove(shift + BITS, hash, key) {
+                    return false;
+                }
+                match child.entries.as_slice() {
+                    [] => {
+                        self.bitmap &= !bit;
+                        self.entries.remove(pos);
+                    }
+                    [Entry::Leaf(..) | Entry::Collision(..)] => {
+                        let entry = child.entries.remove(0);
+                        self.entries[pos] = entry;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+}
+
+/// A persistent hash map, implemented as a hash array mapped trie.
+///
+/// This is the frozen form of [`Persistent<HashMap<K, V, S>>`](Persistent).
+/// Unlike [`FrozenMap`](super::super::FrozenMap), it can be updated in place:
+/// [`insert`](Self::insert) and [`remove`](Self::remove) return a new map,
+/// which shares every untouched node with the original.
+///
+/// Hashing and equality are identical to [`FrozenMap`](super::super::FrozenMap).
+pub struct PersistentMap<K: Hash + Eq, V, S: BuildHasher = RandomState> {
+    root: Arc<Node<K, V>>,
+    len: usize,
+    hasher: S,
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default> PersistentMap<K, V, S> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher> PersistentMap<K, V, S> {
+    /// Create an empty map which will use the given hasher to hash keys.
+    pub fn with_hasher(hasher: S) -> Self {
+        PersistentMap {
+            root: Arc::new(Node::new()),
+            len: 0,
+            hasher,
+        }
+    }
+
+    /// The number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The map's hasher.
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// Get the value corresponding to `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+
+    /// Get the entry corresponding to `key`.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.root
+            .get(self.hasher.hash_one(key), key)
+            .map(|(key, value)| (key, value))
+    }
+
+    /// Whether the map contains `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).is_some()
+    }
+
+    /// An iterator over the entries of the map, in arbitrary order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: vec![self.root.entries.iter()],
+            collision: [].iter(),
+            remaining: self.len,
+        }
+    }
+
+    /// An iterator over the keys of the map, in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// An iterator over the values of the map, in arbitrary order.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Whether both maps share the same root node, and so are equal.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.root, &other.root)
+    }
+
+    /// Insert an entry in place, copying only the nodes shared with other
+    /// maps. Returns whether the key was new.
+    pub(crate) fn insert_mut(&mut self, key: K, value: V) -> bool {
+        let hash = self.hasher.hash_one(&key);
+        let added =
+            Arc::make_mut(&mut self.root).insert(0, hash, Arc::new((key, value)));
+        if added {
+            self.len += 1;
+        }
+        added
+    }
+
+    /// Remove an entry in place, copying only the nodes shared with other
+    /// maps. Returns whether the key was present.
+    pub(crate) fn remove_mut<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.contains_key(key) {
+            return false;
+        }
+        let hash = self.hasher.hash_one(key);
+        Arc::make_mut(&mut self.root).remove(0, hash, key);
+        self.len -= 1;
+        true
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PersistentMap<K, V, S> {
+    /// Return a copy of this map with `key` set to `value`.
+    ///
+    /// Only the nodes on the path to `key` are copied; the rest are shared.
+    #[must_use]
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let mut map = self.clone();
+        map.insert_mut(key, value);
+        map
+    }
+
+    /// Return a copy of this map without `key`.
+    ///
+    /// Only the nodes on the path to `key` are copied; the rest are shared.
+    #[must_use]
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut map = self.clone();
+        map.remove_mut(key);
+        map
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> Clone for PersistentMap<K, V, S> {
+    fn clone(&self) -> Self {
+        PersistentMap {
+            root: self.root.clone(),
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default> Default for PersistentMap<K, V, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Hash + Eq + Debug, V: Debug, S: BuildHasher> Debug for PersistentMap<K, V, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "PersistentMap(")?;
+        f.debug_map().entries(self.iter()).finish()?;
+        write!(f, ")")
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for PersistentMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && (self.ptr_eq(other)
+                || self.iter().all(|(key, value)| {
+                    other.get(key).is_some_and(|other| value == other)
+                }))
+    }
+}
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for PersistentMap<K, V, S> {
+}
+impl<K: Hash + Eq, V: Hash, S: BuildHasher> Hash for PersistentMap<K, V, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.iter(), state);
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher, B: Freezable> Index<B> for PersistentMap<K, V, S>
+where
+    K: Borrow<Frozen<B>>,
+    B::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Output = V;
+
+    fn index(&self, index: B) -> &Self::Output {
+        self.get(&index.freeze())
+            .expect("key not found in PersistentMap")
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)>
+    for PersistentMap<K, V, S>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for PersistentMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert_mut(key, value);
+        }
+    }
+}
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a PersistentMap<K, V, S> {
+    type IntoIter = Iter<'a, K, V>;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+/// Entries still shared with other maps are cloned.
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher> IntoIterator
+    for PersistentMap<K, V, S>
+{
+    type IntoIter = IntoIter<K, V>;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            stack: vec![Arc::unwrap_or_clone(self.root).entries.into_iter()],
+            collision: Vec::new().into_iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+/// An iterator over the entries of a [`PersistentMap`].
+pub struct Iter<'a, K, V> {
+    stack: Vec<slice::Iter<'a, Entry<K, V>>>,
+    collision: slice::Iter<'a, Arc<(K, V)>>,
+    remaining: usize,
+}
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(kv) = self.collision.next() {
+                self.remaining -= 1;
+                return Some((&kv.0, &kv.1));
+            }
+            match self.stack.last_mut()?.next() {
+                None => {
+                    self.stack.pop();
+                }
+                Some(Entry::Leaf(_, kv)) => {
+                    self.remaining -= 1;
+                    return Some((&kv.0, &kv.1));
+                }
+                Some(Entry::Collision(_, kvs)) => self.collision = kvs.iter(),
+                Some(Entry::Branch(node)) => self.stack.push(node.entries.iter()),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+}
+impl<K, V> FusedIterator for Iter<'_, K, V> {
+}
+
+/// An owning iterator over the entries of a [`PersistentMap`].
+pub struct IntoIter<K, V> {
+    stack: Vec<vec::IntoIter<Entry<K, V>>>,
+    collision: vec::IntoIter<Arc<(K, V)>>,
+    remaining: usize,
+}
+impl<K: Clone, V: Clone> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(kv) = self.collision.next() {
+                self.remaining -= 1;
+                return Some(Arc::unwrap_or_clone(kv));
+            }
+            match self.stack.last_mut()?.next() {
+                None => {
+                    self.stack.pop();
+                }
+                Some(Entry::Leaf(_, kv)) => {
+                    self.remaining -= 1;
+                    return Some(Arc::unwrap_or_clone(kv));
+                }
+                Some(Entry::Collision(_, kvs)) => {
+                    self.collision = Arc::unwrap_or_clone(kvs).into_iter();
+                }
+                Some(Entry::Branch(node)) => {
+                    self.stack
+                        .push(Arc::unwrap_or_clone(node).entries.into_iter());
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+impl<K: Clone, V: Clone> ExactSizeIterator for IntoIter<K, V> {
+}
+impl<K: Clone, V: Clone> FusedIterator for IntoIter<K, V> {
+}
+
+/// Freeze a [`HashMap`] into a [`PersistentMap`].
+///
+/// The persistent map keeps a clone of the original map's hasher.
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone> Freezable
+    for Persistent<HashMap<K, V, S>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    type Frozen = PersistentMap<Frozen<K>, Frozen<V>, S>;
+
+    fn freeze(self) -> Frozen<Self> {
+        let mut map = PersistentMap::with_hasher(self.0.hasher().clone());
+        map.extend(self.0.into_iter().map(|(k, v)| (k.freeze(), v.freeze())));
+        Frozen::new(map)
+    }
+}
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone>
+    Frozen<Persistent<HashMap<K, V, S>>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    /// Return a copy of this map with `key` set to `value`, sharing all
+    /// untouched nodes with this map.
+    #[must_use]
+    pub fn insert(&self, key: K, value: V) -> Self {
+        Frozen::new(self.0.insert(key.freeze(), value.freeze()))
+    }
+
+    /// Return a copy of this map without `key`, sharing all untouched nodes
+    /// with this map.
+    #[must_use]
+    pub fn remove(&self, key: K) -> Self {
+        Frozen::new(self.0.remove(&key.freeze()))
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher + Clone,
+    > Unfreezable<Persistent<HashMap<K, V, S>>> for HashMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable + Clone,
+    V::Frozen: Clone,
+{
+    fn thaw(
+        val: <Persistent<HashMap<K, V, S>> as Freezable>::Frozen,
+    ) -> HashMap<RK, RV, S> {
+        let mut map =
+            HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+        map.extend(val.into_iter().map(|(k, v)| (k.thaw(), v.thaw())));
+        map
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + Unfreezable<K>,
+        RV: Unfreezable<V>,
+        S: BuildHasher + Clone,
+    > Unfreezable<Persistent<HashMap<K, V, S>>> for Persistent<HashMap<RK, RV, S>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable + Clone,
+    V::Frozen: Clone,
+{
+    fn thaw(val: <Persistent<HashMap<K, V, S>> as Freezable>::Frozen) -> Self {
+        Persistent(Unfreezable::<Persistent<HashMap<K, V, S>>>::thaw(val))
+    }
+}
+impl<
+        K: Freezable,
+        V: Freezable,
+        RK: Hash + Eq + CloneUnfreezable<K>,
+        RV: CloneUnfreezable<V>,
+        S: BuildHasher + Clone,
+    > CloneUnfreezable<Persistent<HashMap<K, V, S>>> for HashMap<RK, RV, S>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn thaw_cloned(val: &<Persistent<HashMap<K, V, S>> as Freezable>::Frozen) -> Self {
+        let mut map =
+            HashMap::with_capacity_and_hasher(val.len(), val.hasher().clone());
+        map.extend(val.iter().map(|(k, v)| (k.to_thawed(), v.to_thawed())));
+        map
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for Node<K, V> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.entries.heap_size_with(shared)
+    }
+}
+impl<K: HeapSize, V: HeapSize> HeapSize for Entry<K, V> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        match self {
+            Entry::Leaf(_, kv) => kv.heap_size_with(shared),
+            Entry::Collision(_, kvs) => kvs.heap_size_with(shared),
+            Entry::Branch(node) => node.heap_size_with(shared),
+        }
+    }
+}
+/// Nodes shared with other maps are only counted once per measurement.
+impl<K: Hash + Eq + HeapSize, V: HeapSize, S: BuildHasher> HeapSize
+    for PersistentMap<K, V, S>
+{
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.root.heap_size_with(shared)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasherDefault;
+
+    use super::*;
+
+    /// A key whose hash is chosen by the test, so that keys can share hash
+    /// prefixes or collide outright.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Key {
+        hash: u64,
+        id: u32,
+    }
+    impl Hash for Key {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            state.write_u64(self.hash);
+        }
+    }
+
+    /// Hashes a [`Key`] to its chosen hash.
+    #[derive(Default)]
+    struct KeyHasher(u64);
+    impl Hasher for KeyHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, _: &[u8]) {
+            unreachable!("keys only write a u64");
+        }
+
+        fn write_u64(&mut self, hash: u64) {
+            self.0 = hash;
+        }
+    }
+
+    type Map = PersistentMap<Key, u32, BuildHasherDefault<KeyHasher>>;
+
+    fn key(hash: u64, id: u32) -> Key {
+        Key { hash, id }
+    }
+
+    #[test]
+    fn insert_branches_on_shared_prefixes() {
+        // The hashes share their lowest 5 and 10 bits, so the trie needs
+        // three levels to tell them apart.
+        let keys = [key(1, 0), key(1 | 2 << 5, 1), key(1 | 2 << 5 | 3 << 10, 2)];
+        let mut map = Map::new();
+        for (value, key) in (0..).zip(&keys) {
+            assert!(map.insert_mut(key.clone(), value));
+        }
+        assert_eq!(map.len(), 3);
+        for (value, key) in (0..).zip(&keys) {
+            assert_eq!(map.get(key), Some(&value));
+        }
+        assert_eq!(map.get(&key(1 | 3 << 10, 0)), None);
+        assert!(matches!(map.root.entries.as_slice(), [Entry::Branch(_)]));
+
+        assert!(!map.insert_mut(keys[1].clone(), 10));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&keys[1]), Some(&10));
+    }
+
+    #[test]
+    fn insert_and_remove_leave_the_original_unchanged() {
+        let map: Map = (0..100).map(|i| (key(i, 0), i as u32)).collect();
+        let inserted = map.insert(key(1000, 0), 1000);
+        let removed = map.remove(&key(50, 0));
+        assert_eq!((map.len(), inserted.len(), removed.len()), (100, 101, 99));
+        assert_eq!(map.get(&key(1000, 0)), None);
+        assert_eq!(map.get(&key(50, 0)), Some(&50));
+        assert_eq!(inserted.get(&key(1000, 0)), Some(&1000));
+        assert_eq!(removed.get(&key(50, 0)), None);
+        assert_eq!(map.remove(&key(100, 0)), map);
+    }
+
+    #[test]
+    fn remove_collapses_branches() {
+        let deep = key(7 | 7 << 5 | 7 << 10, 1);
+        let map: Map = [(key(7, 0), 0), (deep.clone(), 1)].into_iter().collect();
+        assert!(matches!(map.root.entries.as_slice(), [Entry::Branch(_)]));
+        let map = map.remove(&key(7, 0));
+        assert!(matches!(map.root.entries.as_slice(), [Entry::Leaf(..)]));
+        assert_eq!(map.get(&deep), Some(&1));
+        assert!(map.remove(&deep).root.entries.is_empty());
+    }
+
+    #[test]
+    fn colliding_keys_share_an_entry() {
+        let mut map = Map::new();
+        for id in 0..3 {
+            assert!(map.insert_mut(key(42, id), id));
+        }
+        assert!(matches!(
+            map.root.entries.as_slice(),
+            [Entry::Collision(42, kvs)] if kvs.len() == 3
+        ));
+        assert!(!map.insert_mut(key(42, 1), 10));
+        assert_eq!(map.get(&key(42, 1)), Some(&10));
+        assert_eq!(map.get(&key(42, 3)), None);
+        assert_eq!(map.iter().count(), 3);
+
+        assert!(!map.remove_mut(&key(42, 3)));
+        assert!(map.remove_mut(&key(42, 0)));
+        assert!(map.remove_mut(&key(42, 2)));
+        assert!(matches!(map.root.entries.as_slice(), [Entry::Leaf(42, _)]));
+        assert_eq!(map.get(&key(42, 1)), Some(&10));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn collisions_below_a_branch() {
+        // Two keys collide, and a third shares their lowest 5 bits.
+        let map: Map = [(key(3, 0), 0), (key(3, 1), 1), (key(3 | 1 << 5, 2), 2)]
+            .into_iter()
+            .collect();
+        assert_eq!(map.len(), 3);
+        for id in 0..2 {
+            assert_eq!(map.get(&key(3, id)), Some(&id));
+        }
+        let map = map.remove(&key(3 | 1 << 5, 2));
+        assert!(matches!(
+            map.root.entries.as_slice(),
+            [Entry::Collision(3, _)]
+        ));
+        assert_eq!(map.iter().count(), 2);
+    }
+
+    #[test]
+    fn matches_hash_map() {
+        // Few distinct hashes, so most entries collide.
+        let mut state = 1u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            state >> 33
+        };
+        let mut model = HashMap::new();
+        let mut map = Map::new();
+        for _ in 0..2000 {
+            let key = key(next() % 97, (next() % 5) as u32);
+            if next() % 3 == 0 {
+                assert_eq!(map.remove_mut(&key), model.remove(&key).is_some());
+            } else {
+                let value = next() as u32;
+                assert_eq!(
+                    map.insert_mut(key.clone(), value),
+                    model.insert(key.clone(), value).is_none()
+                );
+            }
+            assert_eq!(map.len(), model.len());
+        }
+        for (key, value) in &model {
+            assert_eq!(map.get(key), Some(value));
+        }
+        assert_eq!(map.iter().count(), model.len());
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use crate::collections::{FrozenMap, FrozenSet};
+use crate::convert::FromElements;
+use crate::frozen_alloc::convert::sort_entries;
+use crate::{DeepImmutable, Freezable, Frozen};
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromElements for HashSet<T, S> {
+    type Element = T;
+}
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromElements for HashMap<K, V, S> {
+    type Element = (K, V);
+}
+
+impl<T: Freezable, S: BuildHasher + Clone> From<Frozen<HashSet<T, S>>>
+    for Frozen<BTreeSet<T>>
+where
+    T::Frozen: Hash + Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<HashSet<T, S>>) -> Self {
+        frozen.verify();
+        let mut sorted: Vec<_> = frozen.into_inner().into_iter().collect();
+        sorted.sort_unstable();
+        Frozen::new(sorted)
+    }
+}
+impl<T: Freezable, S: BuildHasher + Clone + Default> From<Frozen<BTreeSet<T>>>
+    for Frozen<HashSet<T, S>>
+where
+    T::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn from(frozen: Frozen<BTreeSet<T>>) -> Self {
+        frozen.verify();
+        Frozen::new_verified(FrozenSet::from_elements(
+            frozen.into_inner().into_iter(),
+            S::default(),
+        ))
+    }
+}
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone> From<Frozen<HashMap<K, V, S>>>
+    for Frozen<BTreeMap<K, V>>
+where
+    K::Frozen: Hash + Ord + DeepImmutable,
+{
+    fn from(frozen: Frozen<HashMap<K, V, S>>) -> Self {
+        frozen.verify();
+        let mut entries: Vec<_> = frozen.into_inner().into_iter().collect();
+        sort_entries(&mut entries);
+        Frozen::new(entries.into_iter().map(Frozen::new).collect())
+    }
+}
+impl<K: Freezable, V: Freezable, S: BuildHasher + Clone + Default>
+    From<Frozen<BTreeMap<K, V>>> for Frozen<HashMap<K, V, S>>
+where
+    K::Frozen: Hash + Eq + DeepImmutable,
+{
+    fn from(frozen: Frozen<BTreeMap<K, V>>) -> Self {
+        frozen.verify();
+        Frozen::new(FrozenMap::from_entries(
+            frozen.into_inner().into_iter().map(Frozen::into_inner),
+            S::default(),
+        ))
+    }
+}
@@ -0,0 +1,40 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::collections::{FrozenMap, FrozenSet, PersistentMap};
+use crate::diff::{FrozenDiff, MapPatch};
+use crate::frozen_alloc::diff::{diff_entries, impl_hash_diff};
+
+impl_hash_diff!(FrozenMap, FrozenSet);
+
+/// Patching shares every untouched node with the original map.
+impl<K: Hash + Eq + Clone, V: FrozenDiff + Clone, S: BuildHasher + Clone> FrozenDiff
+    for PersistentMap<K, V, S>
+{
+    type Patch = MapPatch<K, V>;
+
+    fn diff(&self, new: &Self) -> Option<Self::Patch> {
+        if self.ptr_eq(new) {
+            return None;
+        }
+        diff_entries(
+            self.iter(),
+            new.iter(),
+            |key| self.get(key),
+            |key| new.get(key),
+        )
+    }
+
+    fn patch(&self, patch: &Self::Patch) -> Self {
+        let mut map = self.clone();
+        for key in &patch.removed {
+            map.remove_mut(key);
+        }
+        for (key, change) in &patch.changed {
+            if let Some(value) = self.get(key) {
+                map.insert_mut(key.clone(), value.patch(change));
+            }
+        }
+        map.extend(patch.added.iter().cloned());
+        map
+    }
+}
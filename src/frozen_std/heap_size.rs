@@ -0,0 +1,82 @@
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "hashbrown")]
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+use crate::collections::{Bag, MultiMap};
+#[cfg(feature = "hashbrown")]
+use crate::collections::{FrozenIndexSet, IndexedMap, IndexedSet};
+use crate::heap_size::{HeapSize, SharedAllocations};
+
+/// The width of the control groups in `std`'s hash tables.
+const GROUP_WIDTH: usize = 16;
+
+/// Estimate the allocation of a `std` hash table holding `T`s, from its
+/// capacity.
+///
+/// `std` does not expose the size of its tables, so this follows the layout
+/// of the `hashbrown` tables it is built on: one slot and one control byte
+/// per bucket, with buckets kept at most 7/8 full.
+fn hash_table_size<T>(capacity: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = if capacity < 8 {
+        capacity + 1
+    } else {
+        capacity / 7 * 8
+    };
+    let align = mem::align_of::<T>().max(GROUP_WIDTH);
+    (buckets * mem::size_of::<T>()).next_multiple_of(align) + buckets + GROUP_WIDTH
+}
+
+/// The table size is estimated from its capacity.
+impl<K: HeapSize, V: HeapSize, S> HeapSize for HashMap<K, V, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        hash_table_size::<(K, V)>(self.capacity())
+            + self
+                .iter()
+                .map(|(key, value)| {
+                    key.heap_size_with(shared) + value.heap_size_with(shared)
+                })
+                .sum::<usize>()
+    }
+}
+/// The table size is estimated from its capacity.
+impl<T: HeapSize, S> HeapSize for HashSet<T, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        hash_table_size::<T>(self.capacity())
+            + self
+                .iter()
+                .map(|item| item.heap_size_with(shared))
+                .sum::<usize>()
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: Hash + Eq + HeapSize, S: BuildHasher> HeapSize for FrozenIndexSet<T, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<K: HeapSize, V: HeapSize, S> HeapSize for IndexedMap<K, V, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+#[cfg(feature = "hashbrown")]
+impl<T: HeapSize, S> HeapSize for IndexedSet<T, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+impl<T: HeapSize, S> HeapSize for Bag<T, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+impl<K: HeapSize, V: HeapSize, S> HeapSize for MultiMap<K, V, S> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
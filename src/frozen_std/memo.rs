@@ -0,0 +1,249 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::collections::hash_unordered;
+use crate::memo::{hash_map_entries, Cache, FrozenLookup, Lookup};
+use crate::{DeepImmutable, Freezable, FreezesToSelf};
+
+/// A memoized function which can be shared between threads.
+///
+/// The cache is locked only to look up and store results, not while the
+/// function runs, so the function may call the memo recursively, and
+/// threads can compute different results at the same time. Two threads
+/// missing the cache with the same arguments will both call the function;
+/// the first result stored is kept.
+pub struct SharedMemo<F, A: Freezable, R> {
+    function: F,
+    cache: Mutex<Cache<A, R>>,
+}
+impl<F: Fn(&A) -> R, A: FrozenLookup, R: Clone> SharedMemo<F, A, R> {
+    /// Cache every result of `function`.
+    pub fn new(function: F) -> Self {
+        SharedMemo {
+            function,
+            cache: Mutex::new(Cache::new(None)),
+        }
+    }
+
+    /// Cache up to `capacity` results of `function`, evicting the least
+    /// recently used result when full.
+    pub fn bounded(function: F, capacity: usize) -> Self {
+        SharedMemo {
+            function,
+            cache: Mutex::new(Cache::new(Some(capacity))),
+        }
+    }
+
+    /// A panic while the cache was locked cannot have left it inconsistent,
+    /// as it is only locked by this type's methods.
+    fn lock(&self) -> MutexGuard<'_, Cache<A, R>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Call the function with `args`, or return the cached result.
+    pub fn call(&self, args: A) -> R {
+        let hash = {
+            let mut cache = self.lock();
+            let hash = cache.hash(&args);
+            if let Some(result) = cache.touch(hash, &args) {
+                return result.clone();
+            }
+            hash
+        };
+        let result = (self.function)(&args);
+        self.lock().insert(hash, args, result.clone());
+        result
+    }
+
+    /// The cached result for `args`, if there is one. This does not count as
+    /// a use of the result.
+    pub fn get(&self, args: &A) -> Option<R> {
+        let cache = self.lock();
+        cache.get(cache.hash(args), args).cloned()
+    }
+
+    /// The number of cached results.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget every cached result.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+}
+
+impl<T, S> FrozenLookup for HashSet<T, S>
+where
+    T: FreezesToSelf + FrozenLookup + Hash + Eq + DeepImmutable,
+    S: BuildHasher + Clone,
+{
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        hash_unordered(self.iter().map(Lookup), state);
+    }
+
+    fn eq_frozen(&self, frozen: &Self::Frozen) -> bool {
+        self.len() == frozen.len() && frozen.iter().all(|item| self.contains(&**item))
+    }
+}
+impl<K, V, S> FrozenLookup for HashMap<K, V, S>
+where
+    K: FreezesToSelf + FrozenLookup + Hash + Eq + DeepImmutable,
+    V: FrozenLookup,
+    S: BuildHasher + Clone,
+{
+    fn hash_frozen<H: Hasher>(&self, state: &mut H) {
+        hash_map_entries(self.iter(), state);
+    }
+
+    fn eq_frozen(&self, frozen: &Self::Frozen) -> bool {
+        self.len() == frozen.len()
+            && frozen.iter().all(|(key, frozen)| {
+                self.get(&**key)
+                    .is_some_and(|value| value.eq_frozen(frozen))
+            })
+    }
+}
+
+/// Memoize a function definition, caching its results by its frozen
+/// arguments.
+///
+/// The arguments must implement [`Clone`] and
+/// [`FrozenLookup`](crate::memo::FrozenLookup), and the result [`Clone`].
+/// By default each thread has its own cache, holding every result. Prefix
+/// the definition with `capacity = N;` to keep only the `N` most recently
+/// used results, with `shared;` to share one cache between all threads, or
+/// with `shared, capacity = N;` for both.
+///
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// freezable::memoize! {
+///     /// The number of ways to climb `steps` stairs, taking any allowed
+///     /// number of steps at a time.
+///     fn climbs(steps: u64, allowed: BTreeSet<u64>) -> u64 {
+///         if steps == 0 {
+///             return 1;
+///         }
+///         allowed
+///             .iter()
+///             .filter(|&&stride| stride <= steps)
+///             .map(|&stride| climbs(steps - stride, allowed.clone()))
+///             .sum()
+///     }
+/// }
+///
+/// freezable::memoize! {
+///     shared, capacity = 64;
+///     fn fib(n: u64) -> u64 {
+///         if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+///     }
+/// }
+///
+/// assert_eq!(climbs(60, BTreeSet::from([1, 2])), fib(61));
+/// ```
+#[macro_export]
+macro_rules! memoize {
+    (@fn $memo:ident $constructor:ident($($capacity:expr)?);
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $arg_type:ty),* $(,)?) -> $result:ty $body:block
+    ) => {
+        $(#[$attr])*
+        #[allow(unused_parens)]
+        $vis fn $name($($arg: $arg_type),*) -> $result {
+            fn __compute($($arg: $arg_type),*) -> $result $body
+            type __Memo = $crate::memo::$memo<fn(&($($arg_type),*)) -> $result, ($($arg_type),*), $result>;
+            fn __memo() -> __Memo {
+                __Memo::$constructor(
+                    |($($arg),*)| __compute($(::core::clone::Clone::clone($arg)),*)
+                    $(, $capacity)?
+                )
+            }
+            $crate::memoize!(@call $memo __Memo, __memo, ($($arg),*))
+        }
+    };
+    (@call LocalMemo $memo_type:ty, $memo:expr, $args:expr) => {{
+        ::std::thread_local! {
+            static MEMO: $memo_type = $memo();
+        }
+        MEMO.with(|memo| memo.call($args))
+    }};
+    (@call SharedMemo $memo_type:ty, $memo:expr, $args:expr) => {{
+        static MEMO: ::std::sync::LazyLock<$memo_type> = ::std::sync::LazyLock::new($memo);
+        MEMO.call($args)
+    }};
+    (shared, capacity = $capacity:expr; $($definition:tt)*) => {
+        $crate::memoize!(@fn SharedMemo bounded($capacity); $($definition)*);
+    };
+    (shared; $($definition:tt)*) => {
+        $crate::memoize!(@fn SharedMemo new(); $($definition)*);
+    };
+    (capacity = $capacity:expr; $($definition:tt)*) => {
+        $crate::memoize!(@fn LocalMemo bounded($capacity); $($definition)*);
+    };
+    ($($definition:tt)*) => {
+        $crate::memoize!(@fn LocalMemo new(); $($definition)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static LOCAL_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static SHARED_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static BOUNDED_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    crate::memoize! {
+        fn local_fib(n: u64) -> u64 {
+            LOCAL_CALLS.fetch_add(1, Ordering::Relaxed);
+            if n < 2 { n } else { local_fib(n - 1) + local_fib(n - 2) }
+        }
+    }
+
+    crate::memoize! {
+        shared;
+        fn shared_fib(n: u64) -> u64 {
+            SHARED_CALLS.fetch_add(1, Ordering::Relaxed);
+            if n < 2 { n } else { shared_fib(n - 1) + shared_fib(n - 2) }
+        }
+    }
+
+    crate::memoize! {
+        shared, capacity = 2;
+        fn bounded_fib(n: u64) -> u64 {
+            BOUNDED_CALLS.fetch_add(1, Ordering::Relaxed);
+            if n < 2 { n } else { bounded_fib(n - 1) + bounded_fib(n - 2) }
+        }
+    }
+
+    #[test]
+    fn recursive_calls() {
+        // Each argument is computed once, as the recursive calls fill the
+        // cache while the outer calls are still running.
+        assert_eq!(local_fib(90), 2_880_067_194_370_816_120);
+        assert_eq!(LOCAL_CALLS.load(Ordering::Relaxed), 91);
+        assert_eq!(local_fib(90), 2_880_067_194_370_816_120);
+        assert_eq!(LOCAL_CALLS.load(Ordering::Relaxed), 91);
+
+        assert_eq!(shared_fib(90), 2_880_067_194_370_816_120);
+        assert_eq!(SHARED_CALLS.load(Ordering::Relaxed), 91);
+    }
+
+    #[test]
+    fn recursive_calls_with_eviction() {
+        // Nested calls evict results and insert arguments the outer calls
+        // are still computing, which must leave the cache consistent.
+        assert_eq!(bounded_fib(30), 832_040);
+        assert!(BOUNDED_CALLS.load(Ordering::Relaxed) > 31);
+        let calls = BOUNDED_CALLS.load(Ordering::Relaxed);
+        assert_eq!(bounded_fib(30), 832_040);
+        assert_eq!(BOUNDED_CALLS.load(Ordering::Relaxed), calls);
+    }
+}
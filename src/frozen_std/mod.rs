@@ -1,4 +1,13 @@
-pub mod boxed;
+pub mod archive;
+pub mod canonical;
 pub mod cell;
 pub mod collections;
-pub mod string;
+pub mod convert;
+pub mod diff;
+pub mod heap_size;
+#[cfg(feature = "hashbrown")]
+pub mod memo;
+pub mod sync;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod watch;
@@ -0,0 +1,201 @@
+//! Sharing frozen snapshots between threads.
+use std::fmt::{self, Debug, Formatter};
+use std::hint;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{Freezable, Frozen};
+
+/// A slot holding the latest frozen snapshot of a `T`, which readers access
+/// without locking.
+///
+/// Readers [`load`](Self::load) an [`Arc`] of the current snapshot without
+/// blocking, while writers publish replacements with [`store`](Self::store),
+/// [`swap`](Self::swap) or [`rcu`](Self::rcu). A replaced snapshot is dropped
+/// once the last [`Arc`] loaded from it is gone.
+///
+/// Only reads are lock-free: writers are serialised by a [`Mutex`], and may
+/// briefly spin while a `load` which started before the snapshot was replaced
+/// finishes taking its reference. Loads which start later are counted
+/// separately, so a steady stream of readers cannot hold a writer up.
+pub struct FrozenCell<T: Freezable> {
+    /// Owns one strong reference, created by [`Arc::into_raw`].
+    ptr: AtomicPtr<Frozen<T>>,
+    /// Counts replaced snapshots. Its parity picks which of `readers` new
+    /// `load`s count themselves in.
+    epoch: AtomicUsize,
+    /// The number of `load`s between reading `ptr` and taking a reference,
+    /// split by the epoch they started in.
+    readers: [AtomicUsize; 2],
+    /// Held while replacing the snapshot.
+    writer: Mutex<()>,
+    _marker: PhantomData<Arc<Frozen<T>>>,
+}
+impl<T: Freezable> FrozenCell<T> {
+    /// Create a cell holding `value`.
+    pub fn new(value: impl Into<Arc<Frozen<T>>>) -> Self {
+        FrozenCell {
+            ptr: AtomicPtr::new(Arc::into_raw(value.into()).cast_mut()),
+            epoch: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            writer: Mutex::new(()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the current snapshot.
+    pub fn load(&self) -> Arc<Frozen<T>> {
+        let readers = loop {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            let readers = &self.readers[epoch & 1];
+            readers.fetch_add(1, Ordering::SeqCst);
+            // If a writer moved to the other epoch meanwhile, it may not wait
+            // for this `load`, so count it in the new epoch instead.
+            if self.epoch.load(Ordering::SeqCst) == epoch {
+                break readers;
+            }
+            readers.fetch_sub(1, Ordering::Release);
+        };
+        let ptr = self.ptr.load(Ordering::SeqCst);
+        // SAFETY: `ptr` came from `Arc::into_raw`, and any writer which has
+        // replaced it since is waiting for `readers` to reach zero before
+        // releasing the cell's reference.
+        let value = unsafe {
+            Arc::increment_strong_count(ptr);
+            Arc::from_raw(ptr)
+        };
+        readers.fetch_sub(1, Ordering::Release);
+        value
+    }
+
+    /// Take back the cell's reference to a snapshot it no longer holds.
+    ///
+    /// Moves new `load`s to the other epoch, then waits for those in the old
+    /// one: they are the only ones which can have read `ptr`, as a `load`
+    /// which confirms the new epoch after counting itself also sees the new
+    /// snapshot.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been removed from `self.ptr` by the caller, while
+    /// holding `self.writer`.
+    unsafe fn retire(&self, ptr: *mut Frozen<T>) -> Arc<Frozen<T>> {
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst) & 1;
+        while self.readers[epoch].load(Ordering::SeqCst) != 0 {
+            hint::spin_loop();
+        }
+        Arc::from_raw(ptr)
+    }
+
+    fn lock_writer(&self) -> MutexGuard<'_, ()> {
+        // The guarded state is the cell itself, which a panicking writer
+        // leaves consistent.
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Replace the current snapshot with `value`, returning the old one.
+    pub fn swap(&self, value: impl Into<Arc<Frozen<T>>>) -> Arc<Frozen<T>> {
+        let new = Arc::into_raw(value.into()).cast_mut();
+        let _writer = self.lock_writer();
+        let old = self.ptr.swap(new, Ordering::SeqCst);
+        // SAFETY: `old` was just swapped out, under the writer lock.
+        unsafe { self.retire(old) }
+    }
+
+    /// Replace the current snapshot with `value`.
+    pub fn store(&self, value: impl Into<Arc<Frozen<T>>>) {
+        self.swap(value);
+    }
+
+    /// Replace the current snapshot with one computed from it, returning the
+    /// old snapshot.
+    ///
+    /// If another writer replaces the snapshot while `f` runs, `f` is called
+    /// again with the newer snapshot.
+    pub fn rcu(&self, mut f: impl FnMut(&Frozen<T>) -> Frozen<T>) -> Arc<Frozen<T>> {
+        let mut old = self.load();
+        loop {
+            let new = Arc::into_raw(Arc::new(f(&old))).cast_mut();
+            let writer = self.lock_writer();
+            match self.ptr.compare_exchange(
+                Arc::as_ptr(&old).cast_mut(),
+                new,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(ptr) => {
+                    // SAFETY: `ptr` was just swapped out, under the writer
+                    // lock.
+                    drop(unsafe { self.retire(ptr) });
+                    return old;
+                }
+                Err(_) => {
+                    drop(writer);
+                    // SAFETY: `new` was never published.
+                    drop(unsafe { Arc::from_raw(new) });
+                    old = self.load();
+                }
+            }
+        }
+    }
+
+    /// Get the current snapshot, consuming the cell.
+    pub fn into_inner(mut self) -> Arc<Frozen<T>> {
+        let ptr = *self.ptr.get_mut();
+        std::mem::forget(self);
+        // SAFETY: the cell is gone, so nothing else can release its reference.
+        unsafe { Arc::from_raw(ptr) }
+    }
+}
+impl<T: Freezable> Drop for FrozenCell<T> {
+    fn drop(&mut self) {
+        // SAFETY: `&mut self` means no `load` is in progress.
+        drop(unsafe { Arc::from_raw(*self.ptr.get_mut()) });
+    }
+}
+impl<T: Freezable> From<Frozen<T>> for FrozenCell<T> {
+    fn from(value: Frozen<T>) -> Self {
+        FrozenCell::new(value)
+    }
+}
+impl<T: Freezable> Debug for FrozenCell<T>
+where
+    Frozen<T>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenCell").field(&self.load()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    use super::*;
+    use crate::Freezable;
+
+    #[test]
+    fn writers_progress_under_constant_loads() {
+        let cell = FrozenCell::<u64>::new(0.freeze());
+        let stop = AtomicBool::new(false);
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let mut last = 0;
+                    while !stop.load(Ordering::Relaxed) {
+                        let value = cell.load().to_thawed::<u64>();
+                        assert!(value >= last);
+                        last = value;
+                    }
+                });
+            }
+            for value in 1..=10_000u64 {
+                cell.store(value.freeze());
+            }
+            stop.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(cell.load().to_thawed::<u64>(), 10_000);
+    }
+}
@@ -0,0 +1,18 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use crate::frozen_alloc::testing::arbitrary_collection;
+use crate::testing::{Arbitrary, Rng};
+
+impl<T: Hash + Eq + Arbitrary, S: BuildHasher + Default> Arbitrary for HashSet<T, S> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        arbitrary_collection(rng)
+    }
+}
+impl<K: Hash + Eq + Arbitrary, V: Arbitrary, S: BuildHasher + Default> Arbitrary
+    for HashMap<K, V, S>
+{
+    fn arbitrary(rng: &mut Rng) -> Self {
+        arbitrary_collection(rng)
+    }
+}
@@ -0,0 +1,213 @@
+//! A channel which broadcasts the latest frozen snapshot to many receivers.
+//!
+//! Every snapshot sent is given the next version number, starting from 0 for
+//! the initial value. Receivers can look at the latest snapshot at any time,
+//! or block until a version newer than the last one they saw is sent.
+//! Intermediate versions may be skipped if several are sent in between.
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::{Freezable, Frozen};
+
+struct State<T: Freezable> {
+    value: Arc<Frozen<T>>,
+    version: u64,
+    closed: bool,
+}
+
+struct Shared<T: Freezable> {
+    state: Mutex<State<T>>,
+    changed: Condvar,
+}
+impl<T: Freezable> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // The lock is never held while running user code, so a poisoned lock
+        // still holds a consistent state.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The latest snapshot and its version number, taken without holding the
+    /// lock afterwards, so that the snapshot can be formatted.
+    fn snapshot(&self) -> (Arc<Frozen<T>>, u64) {
+        let state = self.lock();
+        (state.value.clone(), state.version)
+    }
+}
+
+/// The error returned when waiting on a channel whose [`Sender`] is gone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Closed;
+impl Display for Closed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("watch channel closed")
+    }
+}
+impl Error for Closed {
+}
+
+/// Create a channel holding `initial` as version 0.
+pub fn channel<T: Freezable>(initial: Frozen<T>) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            value: Arc::new(initial),
+            version: 0,
+            closed: false,
+        }),
+        changed: Condvar::new(),
+    });
+    let receiver = Receiver {
+        shared: shared.clone(),
+        seen: 0,
+    };
+    (Sender { shared }, receiver)
+}
+
+/// The sending half of a [`channel`].
+///
+/// Dropping the sender closes the channel. Receivers can still see the last
+/// snapshot sent.
+pub struct Sender<T: Freezable> {
+    shared: Arc<Shared<T>>,
+}
+impl<T: Freezable> Sender<T> {
+    /// Publish a new snapshot, waking every waiting receiver. Returns its
+    /// version number.
+    pub fn send(&self, value: impl Into<Arc<Frozen<T>>>) -> u64 {
+        let value = value.into();
+        let mut state = self.shared.lock();
+        let old = std::mem::replace(&mut state.value, value);
+        state.version += 1;
+        let version = state.version;
+        drop(state);
+        self.shared.changed.notify_all();
+        // The old snapshot is dropped outside the lock.
+        drop(old);
+        version
+    }
+
+    /// The latest snapshot.
+    pub fn borrow(&self) -> Arc<Frozen<T>> {
+        self.shared.lock().value.clone()
+    }
+
+    /// The version number of the latest snapshot.
+    pub fn version(&self) -> u64 {
+        self.shared.lock().version
+    }
+
+    /// Create a receiver which has seen the latest snapshot.
+    pub fn subscribe(&self) -> Receiver<T> {
+        Receiver {
+            shared: self.shared.clone(),
+            seen: self.version(),
+        }
+    }
+}
+impl<T: Freezable> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_all();
+    }
+}
+impl<T: Freezable> Debug for Sender<T>
+where
+    Frozen<T>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (value, version) = self.shared.snapshot();
+        f.debug_struct("Sender")
+            .field("value", &value)
+            .field("version", &version)
+            .finish()
+    }
+}
+
+/// The receiving half of a [`channel`].
+///
+/// Cloning a receiver gives one which has seen the same version.
+pub struct Receiver<T: Freezable> {
+    shared: Arc<Shared<T>>,
+    seen: u64,
+}
+impl<T: Freezable> Receiver<T> {
+    /// The latest snapshot, without marking it as seen.
+    pub fn borrow(&self) -> Arc<Frozen<T>> {
+        self.shared.lock().value.clone()
+    }
+
+    /// The latest snapshot, marking it as seen.
+    pub fn borrow_and_update(&mut self) -> Arc<Frozen<T>> {
+        let state = self.shared.lock();
+        self.seen = state.version;
+        state.value.clone()
+    }
+
+    /// The version number of the latest snapshot.
+    pub fn version(&self) -> u64 {
+        self.shared.lock().version
+    }
+
+    /// The version number of the last snapshot marked as seen.
+    pub fn seen_version(&self) -> u64 {
+        self.seen
+    }
+
+    /// Whether a snapshot newer than the last one seen has been sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Closed`] if the sender is gone and there is no newer snapshot.
+    pub fn has_changed(&self) -> Result<bool, Closed> {
+        let state = self.shared.lock();
+        match state.version > self.seen {
+            false if state.closed => Err(Closed),
+            changed => Ok(changed),
+        }
+    }
+
+    /// Block until a snapshot newer than the last one seen is sent, then mark
+    /// it as seen and return its version number.
+    ///
+    /// Returns immediately if such a snapshot has already been sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Closed`] if the sender is dropped without sending a newer
+    /// snapshot.
+    pub fn changed(&mut self) -> Result<u64, Closed> {
+        let state = self
+            .shared
+            .changed
+            .wait_while(self.shared.lock(), |state| {
+                state.version <= self.seen && !state.closed
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        if state.version <= self.seen {
+            return Err(Closed);
+        }
+        self.seen = state.version;
+        Ok(self.seen)
+    }
+}
+impl<T: Freezable> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Receiver {
+            shared: self.shared.clone(),
+            seen: self.seen,
+        }
+    }
+}
+impl<T: Freezable> Debug for Receiver<T>
+where
+    Frozen<T>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (value, version) = self.shared.snapshot();
+        f.debug_struct("Receiver")
+            .field("value", &value)
+            .field("version", &version)
+            .field("seen", &self.seen)
+            .finish()
+    }
+}
@@ -0,0 +1,154 @@
+//! Measuring the memory used by frozen values.
+//!
+//! [`HeapSize`] counts the heap bytes a value owns, however deeply nested:
+//! the full capacity of collections, the contents of boxes, and allocations
+//! shared through [`Rc`](alloc::rc::Rc) or [`Arc`](alloc::sync::Arc), which
+//! are counted once no matter how many times they are reached. Frozen values
+//! report this through [`Frozen::heap_size`] and [`Frozen::total_size`], so
+//! the cost of a frozen form can be compared with the value it was frozen
+//! from.
+//!
+//! Sizes are exact for allocations whose layout is known, and close estimates
+//! for the tables of hash-based collections from `std`.
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeSet;
+use core::mem;
+use core::time::Duration;
+
+use crate::frozen_core::FrozenMutRef;
+use crate::{Freezable, Frozen};
+
+/// A value which can report how much heap memory it owns.
+pub trait HeapSize {
+    /// The number of heap bytes owned by this value, not counting the value
+    /// itself. Shared allocations already recorded in `shared` are not
+    /// counted again.
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize;
+}
+
+/// The shared allocations already counted by a [`HeapSize`] measurement.
+#[derive(Debug, Default)]
+pub struct SharedAllocations {
+    #[cfg(feature = "alloc")]
+    seen: BTreeSet<usize>,
+}
+impl SharedAllocations {
+    /// Start a new measurement.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the shared allocation at `ptr`, returning whether it had not
+    /// been recorded before.
+    #[cfg(feature = "alloc")]
+    pub fn insert<T: ?Sized>(&mut self, ptr: *const T) -> bool {
+        self.seen.insert(ptr.cast::<()>() as usize)
+    }
+}
+
+impl<T: Freezable + ?Sized> Frozen<T>
+where
+    T::Frozen: HeapSize,
+{
+    /// The number of heap bytes owned by this value. See [`HeapSize`].
+    pub fn heap_size(&self) -> usize {
+        self.0.heap_size_with(&mut SharedAllocations::new())
+    }
+
+    /// The number of bytes used by this value, both inline and on the heap.
+    pub fn total_size(&self) -> usize {
+        mem::size_of_val(self) + self.heap_size()
+    }
+}
+
+macro_rules! impl_no_heap {
+    ($($impl_type:ty),*) => {
+        $(
+            impl HeapSize for $impl_type {
+                fn heap_size_with(&self, _shared: &mut SharedAllocations) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+impl_no_heap!(
+    (),
+    bool,
+    char,
+    f32,
+    f64,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    str,
+    Duration
+);
+
+/// Borrowed values are owned elsewhere, so count nothing.
+impl<T: ?Sized> HeapSize for &T {
+    fn heap_size_with(&self, _shared: &mut SharedAllocations) -> usize {
+        0
+    }
+}
+/// Borrowed values are owned elsewhere, so count nothing.
+impl<T: Freezable + ?Sized> HeapSize for FrozenMutRef<'_, T> {
+    fn heap_size_with(&self, _shared: &mut SharedAllocations) -> usize {
+        0
+    }
+}
+
+impl<T: Freezable + ?Sized> HeapSize for Frozen<T>
+where
+    T::Frozen: HeapSize,
+{
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.0.heap_size_with(shared)
+    }
+}
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.as_ref()
+            .map_or(0, |value| value.heap_size_with(shared))
+    }
+}
+impl<T: HeapSize> HeapSize for [T] {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.iter().map(|item| item.heap_size_with(shared)).sum()
+    }
+}
+impl<T: HeapSize, const N: usize> HeapSize for [T; N] {
+    fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+        self.as_slice().heap_size_with(shared)
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($param:ident $index:tt),*) => {
+        impl<$($param: HeapSize),*> HeapSize for ($($param,)*) {
+            fn heap_size_with(&self, shared: &mut SharedAllocations) -> usize {
+                0 $(+ self.$index.heap_size_with(shared))*
+            }
+        }
+    };
+}
+tuple_impl!(A 0, B 1);
+tuple_impl!(A 0, B 1, C 2);
+tuple_impl!(A 0, B 1, C 2, D 3);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+tuple_impl!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
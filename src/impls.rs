@@ -31,7 +31,9 @@ use core::ops::{
     Sub,
 };
 
-use crate::{Freezable, Frozen, Unfreezable};
+#[cfg(feature = "std")]
+use crate::collections::{Bag, FrozenBag, FrozenMultiMap, MultiMap};
+use crate::{CloneUnfreezable, DeepImmutable, Freezable, Frozen, Unfreezable};
 
 impl<T: Freezable + ?Sized> Deref for Frozen<T> {
     type Target = T::Frozen;
@@ -58,13 +60,13 @@ where
     type Item = <<T as Freezable>::Frozen as IntoIterator>::Item;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.into_inner().into_iter()
     }
 }
 
 impl<T: Freezable + ?Sized> Hash for Frozen<T>
 where
-    T::Frozen: Hash,
+    T::Frozen: Hash + DeepImmutable,
 {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.hash(state);
@@ -87,7 +89,7 @@ where
     T::Frozen: Clone,
 {
     fn clone(&self) -> Self {
-        Frozen(self.0.clone())
+        Frozen::new(self.0.clone())
     }
 }
 impl<T: Freezable + ?Sized> Copy for Frozen<T> where T::Frozen: Copy
@@ -95,18 +97,18 @@ impl<T: Freezable + ?Sized> Copy for Frozen<T> where T::Frozen: Copy
 }
 impl<T: Freezable + ?Sized> PartialEq for Frozen<T>
 where
-    T::Frozen: PartialEq,
+    T::Frozen: PartialEq + DeepImmutable,
 {
     fn eq(&self, other: &Self) -> bool {
         self.0.eq(&other.0)
     }
 }
-impl<T: Freezable + ?Sized> Eq for Frozen<T> where T::Frozen: Eq
+impl<T: Freezable + ?Sized> Eq for Frozen<T> where T::Frozen: Eq + DeepImmutable
 {
 }
 impl<T: Freezable + ?Sized> PartialOrd for Frozen<T>
 where
-    T::Frozen: PartialOrd,
+    T::Frozen: PartialOrd + DeepImmutable,
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.0.partial_cmp(&other.0)
@@ -114,7 +116,7 @@ where
 }
 impl<T: Freezable + ?Sized> Ord for Frozen<T>
 where
-    T::Frozen: Ord,
+    T::Frozen: Ord + DeepImmutable,
 {
     fn cmp(&self, other: &Self) -> Ordering {
         self.0.cmp(&other.0)
@@ -164,7 +166,7 @@ macro_rules! impl_op {
             type Output = <T::Frozen as $trait_name<U>>::Output;
 
             fn $trait_fn(self, rhs: U) -> Self::Output {
-                self.0.$trait_fn(rhs)
+                self.into_inner().$trait_fn(rhs)
             }
         }
         impl<'a, T: Freezable + ?Sized, U> $trait_name<U> for &'a Frozen<T>
@@ -215,7 +217,7 @@ where
     type Output = <T::Frozen as Neg>::Output;
 
     fn neg(self) -> Self::Output {
-        -self.0
+        -self.into_inner()
     }
 }
 
@@ -236,13 +238,21 @@ impl<T: Freezable + ?Sized> Freezable for Frozen<T> {
     type Frozen = T::Frozen;
 
     fn freeze(self) -> Frozen<Self> {
-        Frozen(self.0)
+        self.rewrap()
     }
 }
 
 impl<T: Freezable + ?Sized> Unfreezable<Frozen<T>> for Frozen<T> {
     fn thaw(wrapped: <Frozen<T> as Freezable>::Frozen) -> Self {
-        Frozen(wrapped)
+        Frozen::new(wrapped)
+    }
+}
+impl<T: Freezable + ?Sized> CloneUnfreezable<Frozen<T>> for Frozen<T>
+where
+    T::Frozen: Clone,
+{
+    fn thaw_cloned(wrapped: &<Frozen<T> as Freezable>::Frozen) -> Self {
+        Frozen::new(wrapped.clone())
     }
 }
 
@@ -250,6 +260,38 @@ pub trait FreezableIteratorExt<T: Freezable>: Iterator<Item = T> + Sized {
     fn frozen(self) -> Map<Self, fn(T) -> Frozen<T>> {
         self.map(Freezable::freeze)
     }
+
+    /// Count how many times each item appears, freezing the counts into a
+    /// [`FrozenBag`].
+    #[cfg(feature = "std")]
+    fn freeze_counts(self) -> Frozen<Bag<T>>
+    where
+        T::Frozen: Hash + Eq + DeepImmutable,
+    {
+        let mut bag = FrozenBag::with_hasher(Default::default());
+        for item in self {
+            bag.add(item.freeze(), 1);
+        }
+        Frozen::new_verified(bag)
+    }
+
+    /// Group the items by the key `key` gives each of them, freezing the
+    /// groups into a [`FrozenMultiMap`]. Each group keeps its items in the
+    /// order they were visited.
+    #[cfg(feature = "std")]
+    fn freeze_grouped_by<K: Freezable>(
+        self,
+        mut key: impl FnMut(&T) -> K,
+    ) -> Frozen<MultiMap<K, T>>
+    where
+        K::Frozen: Hash + Eq + DeepImmutable,
+    {
+        let mut map = FrozenMultiMap::with_hasher(Default::default());
+        for item in self {
+            map.push(key(&item).freeze(), item.freeze());
+        }
+        Frozen::new(map)
+    }
 }
 
 impl<T: Freezable, I: Iterator<Item = T>> FreezableIteratorExt<T> for I {
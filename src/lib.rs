@@ -1,22 +1,63 @@
-#![feature(proc_macro_hygiene)]
+#![feature(auto_traits, negative_impls, proc_macro_hygiene)]
 #![cfg_attr(not(feature = "std"), no_std)]
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod canonical;
+pub mod convert;
+pub mod diff;
+#[cfg(any(feature = "alloc", feature = "testing", feature = "verify"))]
+mod fnv;
+#[cfg(feature = "alloc")]
+mod frozen_alloc;
 mod frozen_core;
 #[cfg(feature = "std")]
 mod frozen_std;
+pub mod heap_size;
 mod impls;
 pub mod prelude;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod verify;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+use core::any;
+use core::cell::UnsafeCell;
+use core::hash::Hash;
+use core::marker::PhantomData;
+
+#[cfg(feature = "hashbrown")]
+pub use frozen_alloc::memo;
+#[cfg(feature = "alloc")]
+pub use frozen_alloc::{archive, collections, history, value};
+#[cfg(feature = "std")]
+pub use frozen_std::sync;
+#[cfg(feature = "std")]
+pub use frozen_std::watch;
 pub use impls::{FreezableIteratorExt, UnfreezableIteratorExt};
+use verify::StableAddress;
 
 /// The primary trait for the `freezable` crate.
 ///
 /// This trait is implemented for all types that can be frozen, and indicates
 /// the result of freezing this type.
 ///
-/// It is a logic error for a frozen type to have interior mutability.
+/// It is a logic error for a frozen type to have interior mutability. Frozen
+/// types which are not [`DeepImmutable`] cannot be hashed or compared for
+/// equality.
 pub trait Freezable {
     /// The concrete type that is returned when freezing this type.
     type Frozen;
 
+    /// Proof that this type implements [`FreezesToSelf`].
+    ///
+    /// Collections check this to reuse their storage when freezing, instead
+    /// of rebuilding it element by element. Only types implementing
+    /// [`FreezesToSelf`] can set this to `Some`.
+    const SELF_FROZEN: Option<SelfFrozen<Self>> = None;
+
     /// Freeze this type.
     fn freeze(self) -> Frozen<Self>;
 }
@@ -26,14 +67,125 @@ pub trait Freezable {
 /// may be unfreezable to multiple types, and multiple types may unfreeze the
 /// same frozen type
 pub trait Unfreezable<T: Freezable + ?Sized> {
+    /// Proof that this type is `T`, and that `T` implements
+    /// [`FreezesToSelf`].
+    ///
+    /// Collections check this to reuse their storage when thawing, instead of
+    /// rebuilding it element by element.
+    const SELF_FROZEN: Option<SelfFrozen<T, Self>> = None;
+
     /// Unfreeze the frozen type.
     fn thaw(wrapped: T::Frozen) -> Self;
 }
 
+/// Unfreeze a borrowed frozen type, cloning whatever is needed. This is the
+/// borrowing counterpart to [`Unfreezable`], and lets a single frozen value
+/// seed any number of unfrozen copies.
+pub trait CloneUnfreezable<T: Freezable + ?Sized> {
+    /// Unfreeze a clone of the frozen type.
+    fn thaw_cloned(wrapped: &T::Frozen) -> Self;
+}
+
+/// A type which is its own frozen form.
+///
+/// Freezing and thawing such a type must be a no-op. `Vec`s and `VecDeque`s of
+/// these types reuse their existing allocation when frozen and thawed, instead
+/// of being rebuilt element by element. Hash maps and sets of them are still
+/// rebuilt, as a table is not guaranteed to have the same layout once its
+/// entries are wrapped in [`Frozen`].
+pub trait FreezesToSelf: Freezable<Frozen = Self> + Unfreezable<Self> {}
+
+/// A type with no interior mutability, however deeply nested.
+///
+/// This is implemented automatically for every type whose fields are all
+/// `DeepImmutable`. It is not implemented for [`UnsafeCell`], so any type
+/// containing a `Cell`, `RefCell`, `OnceCell`, atomic or lock is rejected.
+///
+/// [`Frozen`] only implements [`Hash`], [`PartialEq`] and
+/// [`Eq`] when the frozen form is `DeepImmutable`, so a frozen value cannot
+/// change its hash or equality while it is used as a key.
+///
+/// # Safety
+///
+/// Implementing this by hand promises that nothing observable through a
+/// shared reference to the type can change.
+pub unsafe auto trait DeepImmutable {}
+impl<T: ?Sized> !DeepImmutable for UnsafeCell<T> {
+}
+/// The reference counts are mutable, but cannot affect the shared value.
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+unsafe impl<T: DeepImmutable + ?Sized> DeepImmutable for Arc<T> {
+}
+/// The reference counts are mutable, but cannot affect the shared value.
+#[cfg(feature = "alloc")]
+unsafe impl<T: DeepImmutable + ?Sized> DeepImmutable for Rc<T> {
+}
+
+/// A witness that `T` and `U` are the same type, which implements
+/// [`FreezesToSelf`].
+///
+/// This can only be constructed through [`SelfFrozen::new`], so holding one
+/// is enough to treat `T` and `U` as interchangeable, along with `Frozen<T>`.
+///
+/// The witness is invariant in both types, so it cannot be used to swap a
+/// type for one with a longer lifetime:
+///
+/// ```compile_fail
+/// use freezable::{Freezable, Frozen, FreezesToSelf, SelfFrozen, Unfreezable};
+///
+/// struct Local<'a>(&'a str);
+/// impl<'a> Freezable for Local<'a> {
+///     type Frozen = Local<'a>;
+///
+///     fn freeze(self) -> Frozen<Self> {
+///         Frozen::new(self)
+///     }
+/// }
+/// impl FreezesToSelf for Local<'static> {}
+/// impl<'a> Unfreezable<Local<'a>> for Local<'static> {
+///     const SELF_FROZEN: Option<SelfFrozen<Local<'a>, Self>> =
+///         Some(SelfFrozen::<Local<'static>>::new());
+///
+///     fn thaw(_: Local<'a>) -> Self {
+///         Local("")
+///     }
+/// }
+///
+/// fn extend(locals: Vec<Local<'_>>) -> Vec<Local<'static>> {
+///     locals.freeze().thaw()
+/// }
+/// ```
+pub struct SelfFrozen<T: ?Sized, U: ?Sized = T>(PhantomData<Invariant<T, U>>);
+/// A type which is invariant in both `T` and `U`.
+type Invariant<T, U> = fn(*const T, *const U) -> (*const T, *const U);
+impl<T: FreezesToSelf> SelfFrozen<T> {
+    /// Construct the witness for a type implementing [`FreezesToSelf`].
+    pub const fn new() -> Self {
+        SelfFrozen(PhantomData)
+    }
+}
+impl<T: FreezesToSelf> Default for SelfFrozen<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: ?Sized, U: ?Sized> Clone for SelfFrozen<T, U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized, U: ?Sized> Copy for SelfFrozen<T, U> {
+}
+
 /// A frozen `T`.
 ///
 /// This type is returned by the `Freezable::freeze` method, and is a standard
 /// wrapper around `T::Frozen`.
+///
+/// With the `verify` feature, a `Frozen` created by
+/// [`new_verified`](Self::new_verified) also has a fingerprint of its contents
+/// recorded, and panics if the contents are found to have changed. See the
+/// [`verify`] module.
 #[repr(transparent)]
 pub struct Frozen<T: Freezable + ?Sized>(T::Frozen);
 impl<T: Freezable + ?Sized> Frozen<T> {
@@ -42,15 +194,79 @@ impl<T: Freezable + ?Sized> Frozen<T> {
     /// Required for implementing [`Unfreezable`] and [`Freezable`] for
     /// a type - not usually useful for user code.
     pub fn new(frozen: T::Frozen) -> Self {
+        verify::forget(&frozen);
+        Frozen(frozen)
+    }
+
+    /// Construct a new `Frozen` from a frozen value, recording a fingerprint
+    /// of its contents with the `verify` feature.
+    ///
+    /// [`Freezable`] impls whose frozen form might have interior mutability
+    /// can use this instead of [`new`](Self::new), so that mutation is caught
+    /// by [`verify`](Self::verify). Without the `verify` feature, or if the
+    /// contents are not on the heap, this is the same as `new`.
+    pub fn new_verified(frozen: T::Frozen) -> Self
+    where
+        T::Frozen: Hash + StableAddress,
+    {
+        verify::record(&frozen);
         Frozen(frozen)
     }
 
+    /// Check that the contents have not changed since this value was frozen.
+    ///
+    /// This does nothing unless the `verify` feature is enabled and this value
+    /// was created by [`new_verified`](Self::new_verified).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the contents have changed, which means `T::Frozen` has
+    /// interior mutability.
+    pub fn verify(&self) {
+        verify::check(&self.0, any::type_name::<T>());
+    }
+
+    /// Convert to another type with the same frozen form, keeping the
+    /// fingerprint.
+    pub(crate) fn rewrap<U: Freezable<Frozen = T::Frozen> + ?Sized>(self) -> Frozen<U> {
+        Frozen(self.0)
+    }
+
+    /// Take the frozen value out, without checking it, and forget its
+    /// fingerprint.
+    pub(crate) fn into_inner(self) -> T::Frozen {
+        verify::forget(&self.0);
+        self.0
+    }
+
     /// Unfreeze this type into some compatible `U`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`verify`](Self::verify) would.
     pub fn thaw<U>(self) -> U
     where
         U: Unfreezable<T>,
     {
-        <U as Unfreezable<T>>::thaw(self.0)
+        self.verify();
+        <U as Unfreezable<T>>::thaw(self.into_inner())
+    }
+
+    /// Unfreeze a copy of this type into some compatible `U`, leaving this
+    /// frozen value untouched.
+    ///
+    /// This builds `U` directly from the frozen value, without first cloning
+    /// the whole frozen value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`verify`](Self::verify) would.
+    pub fn to_thawed<U>(&self) -> U
+    where
+        U: CloneUnfreezable<T>,
+    {
+        self.verify();
+        <U as CloneUnfreezable<T>>::thaw_cloned(&self.0)
     }
 }
 
@@ -59,15 +275,26 @@ macro_rules! impl_self_freezable {
         impl $(<$($lifetime_params),*>)? Freezable for $impl_type {
             type Frozen = Self;
 
+            const SELF_FROZEN: Option<SelfFrozen<Self>> = Some(SelfFrozen::new());
+
             fn freeze(self) -> Frozen<Self> {
-                Frozen(self)
+                Frozen::new(self)
             }
         }
         impl $(<$($lifetime_params),*>)? Unfreezable<$impl_type> for $impl_type {
+            const SELF_FROZEN: Option<SelfFrozen<$impl_type, Self>> = Some(SelfFrozen::new());
+
             fn thaw(wrapped: <Self as Freezable>::Frozen) -> Self {
                 wrapped
             }
         }
+        impl $(<$($lifetime_params),*>)? CloneUnfreezable<$impl_type> for $impl_type {
+            fn thaw_cloned(wrapped: &<Self as Freezable>::Frozen) -> Self {
+                wrapped.clone()
+            }
+        }
+        impl $(<$($lifetime_params),*>)? FreezesToSelf for $impl_type {
+        }
     };
 }
 
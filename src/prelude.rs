@@ -1,4 +1,6 @@
 pub use crate::{
+    CloneUnfreezable,
+    DeepImmutable,
     Freezable,
     FreezableIteratorExt,
     Frozen,
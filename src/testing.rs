@@ -0,0 +1,388 @@
+//! Helpers for checking [`Freezable`] and [`Unfreezable`] implementations.
+//!
+//! Implementations are expected to uphold a few laws which the compiler cannot
+//! check:
+//!
+//! - thawing a frozen value gives back a value equal to the original
+//!   ([`assert_round_trip`]), as does thawing a clone of it
+//!   ([`assert_clone_round_trip`]);
+//! - freezing equal values gives equal frozen forms, which hash the same
+//!   ([`assert_hash_consistent_with_eq`] and [`assert_freeze_deterministic`]);
+//! - the frozen form of an unordered collection does not depend on the order
+//!   its elements were inserted in ([`assert_order_independent`]).
+//!
+//! Each assertion panics with a description of the broken law. They can be
+//! run over many generated values with [`check`], using the [`Arbitrary`]
+//! values produced by the built-in [`Rng`].
+//!
+//! This module requires the `testing` feature.
+//!
+//! ```
+//! use freezable::testing::{self, Rng};
+//!
+//! testing::check(100, |value: Vec<(u8, String)>| {
+//!     testing::assert_clone_round_trip(&value);
+//!     testing::assert_freeze_deterministic(&value);
+//! });
+//!
+//! let mut rng = Rng::new(7);
+//! let elements: Vec<u16> = rng.arbitrary();
+//! testing::assert_order_independent::<std::collections::HashSet<u16>, _>(&elements, &mut rng);
+//! ```
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::{Hash, Hasher};
+use core::time::Duration;
+
+use crate::fnv::Fnv;
+use crate::{CloneUnfreezable, Freezable, Frozen, Unfreezable};
+
+/// A small, deterministic random number generator for producing test values.
+///
+/// This is SplitMix64: fast and well distributed, but not suitable for
+/// anything other than testing.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+    size: usize,
+}
+impl Rng {
+    /// Create a generator from a seed. The same seed always produces the same
+    /// values.
+    pub fn new(seed: u64) -> Self {
+        Rng {
+            state: seed,
+            size: 8,
+        }
+    }
+
+    /// Set the largest number of elements generated for each collection. The
+    /// default is 8.
+    pub fn with_size(self, size: usize) -> Self {
+        Rng { size, ..self }
+    }
+
+    /// The largest number of elements generated for each collection.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Generate the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Generate a number in `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "cannot generate a number below zero");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Generate a number of elements for a collection, up to
+    /// [`size`](Self::size).
+    pub fn collection_len(&mut self) -> usize {
+        self.below(self.size + 1)
+    }
+
+    /// Generate an arbitrary value.
+    pub fn arbitrary<T: Arbitrary>(&mut self) -> T {
+        T::arbitrary(self)
+    }
+
+    /// Shuffle `slice` into a random order.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// A type whose values can be generated by an [`Rng`].
+///
+/// Numbers are drawn from a small range half of the time, so that generated
+/// collections contain duplicates and generated values are often equal.
+/// Floating-point numbers are always finite, so generated values are equal to
+/// themselves.
+pub trait Arbitrary: Sized {
+    /// Generate a value.
+    fn arbitrary(rng: &mut Rng) -> Self;
+}
+
+macro_rules! impl_arbitrary_int {
+    ($($impl_type:ty),*) => {
+        $(
+            impl Arbitrary for $impl_type {
+                fn arbitrary(rng: &mut Rng) -> Self {
+                    if rng.below(2) == 0 {
+                        rng.below(2 * rng.size() + 1) as $impl_type
+                    } else {
+                        rng.next_u64() as $impl_type
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_arbitrary_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl Arbitrary for i128 {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        u128::arbitrary(rng) as i128
+    }
+}
+impl Arbitrary for u128 {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        if rng.below(2) == 0 {
+            u64::arbitrary(rng).into()
+        } else {
+            u128::from(rng.next_u64()) << 64 | u128::from(rng.next_u64())
+        }
+    }
+}
+impl Arbitrary for f32 {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        f32::from(i16::arbitrary(rng)) / 16.0
+    }
+}
+impl Arbitrary for f64 {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        f64::from(i32::arbitrary(rng)) / 256.0
+    }
+}
+impl Arbitrary for bool {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        rng.below(2) == 0
+    }
+}
+/// Mostly ASCII, with any other character a quarter of the time.
+impl Arbitrary for char {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        if rng.below(4) == 0 {
+            loop {
+                if let Some(c) = char::from_u32(rng.below(0x11_0000) as u32) {
+                    return c;
+                }
+            }
+        }
+        char::from(b' ' + rng.below(95) as u8)
+    }
+}
+impl Arbitrary for Duration {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        Duration::new(u64::arbitrary(rng), rng.below(1_000_000_000) as u32)
+    }
+}
+impl Arbitrary for () {
+    fn arbitrary(_rng: &mut Rng) -> Self {
+    }
+}
+impl<T: Arbitrary> Arbitrary for Option<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        if rng.below(4) == 0 {
+            None
+        } else {
+            Some(rng.arbitrary())
+        }
+    }
+}
+impl<T: Arbitrary, const N: usize> Arbitrary for [T; N] {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        core::array::from_fn(|_| rng.arbitrary())
+    }
+}
+impl<T: Freezable + Arbitrary> Arbitrary for Frozen<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        T::arbitrary(rng).freeze()
+    }
+}
+
+macro_rules! tuple_impl {
+    ($($param:ident),*) => {
+        impl<$($param: Arbitrary),*> Arbitrary for ($($param,)*) {
+            fn arbitrary(rng: &mut Rng) -> Self {
+                ($($param::arbitrary(rng),)*)
+            }
+        }
+    };
+}
+tuple_impl!(A);
+tuple_impl!(A, B);
+tuple_impl!(A, B, C);
+tuple_impl!(A, B, C, D);
+tuple_impl!(A, B, C, D, E);
+tuple_impl!(A, B, C, D, E, F);
+tuple_impl!(A, B, C, D, E, F, G);
+tuple_impl!(A, B, C, D, E, F, G, H);
+tuple_impl!(A, B, C, D, E, F, G, H, I);
+tuple_impl!(A, B, C, D, E, F, G, H, I, J);
+tuple_impl!(A, B, C, D, E, F, G, H, I, J, K);
+tuple_impl!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Check a property against `cases` generated values.
+///
+/// Case `i` is generated by `Rng::new(i)`, so a failing case can be
+/// reproduced from its seed. With `std`, the seed and the generated value are
+/// reported when the property panics.
+pub fn check<T: Arbitrary + Debug>(cases: u64, mut property: impl FnMut(T)) {
+    for seed in 0..cases {
+        let value = Rng::new(seed).arbitrary();
+        #[cfg(feature = "std")]
+        {
+            let outcome =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    property(value);
+                }));
+            if let Err(payload) = outcome {
+                let value: T = Rng::new(seed).arbitrary();
+                std::eprintln!("property failed for seed {seed} with {value:?}");
+                std::panic::resume_unwind(payload);
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        property(value);
+    }
+}
+
+/// Hash `value` deterministically.
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = Fnv::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Assert that freezing and thawing `value` gives back an equal value.
+///
+/// # Panics
+///
+/// Panics if the thawed value differs from `value`.
+pub fn assert_round_trip<T>(value: &T)
+where
+    T: Freezable + Unfreezable<T> + Clone + PartialEq + Debug,
+{
+    let thawed: T = value.clone().freeze().thaw();
+    assert!(
+        thawed == *value,
+        "thawing changed the value: froze {value:?}, thawed {thawed:?}",
+    );
+}
+
+/// Assert that thawing a clone of the frozen `value` with
+/// [`Frozen::to_thawed`] gives back an equal value, as well as thawing it
+/// outright.
+///
+/// # Panics
+///
+/// Panics if either thawed value differs from `value`.
+pub fn assert_clone_round_trip<T>(value: &T)
+where
+    T: Freezable + Unfreezable<T> + CloneUnfreezable<T> + Clone + PartialEq + Debug,
+{
+    let frozen = value.clone().freeze();
+    let thawed: T = frozen.to_thawed();
+    assert!(
+        thawed == *value,
+        "thawing a clone changed the value: froze {value:?}, thawed {thawed:?}",
+    );
+    let thawed: T = frozen.thaw();
+    assert!(
+        thawed == *value,
+        "thawing changed the value: froze {value:?}, thawed {thawed:?}",
+    );
+}
+
+/// Assert that the frozen forms of `a` and `b` are equal exactly when `a` and
+/// `b` are, and that equal frozen forms hash the same.
+///
+/// # Panics
+///
+/// Panics if freezing does not preserve equality, or if the frozen forms'
+/// [`Hash`] disagrees with their [`Eq`].
+pub fn assert_hash_consistent_with_eq<T>(a: &T, b: &T)
+where
+    T: Freezable + Clone + PartialEq + Debug,
+    Frozen<T>: Hash + Eq + Debug,
+{
+    let frozen_a = a.clone().freeze();
+    let frozen_b = b.clone().freeze();
+    assert!(
+        (a == b) == (frozen_a == frozen_b),
+        "freezing did not preserve equality: {a:?} {} {b:?}, but {frozen_a:?} {} {frozen_b:?}",
+        if a == b { "==" } else { "!=" },
+        if frozen_a == frozen_b { "==" } else { "!=" },
+    );
+    if frozen_a == frozen_b {
+        assert!(
+            hash_of(&frozen_a) == hash_of(&frozen_b),
+            "equal frozen values hash differently: {frozen_a:?} and {frozen_b:?}",
+        );
+    }
+}
+
+/// Assert that freezing `value` twice gives equal frozen forms with equal
+/// hashes.
+///
+/// # Panics
+///
+/// Panics if the two frozen forms differ.
+pub fn assert_freeze_deterministic<T>(value: &T)
+where
+    T: Freezable + Clone + Debug,
+    Frozen<T>: Hash + Eq + Debug,
+{
+    let first = value.clone().freeze();
+    let second = value.clone().freeze();
+    assert!(
+        first == second,
+        "freezing {value:?} twice gave {first:?} and {second:?}",
+    );
+    assert!(
+        hash_of(&first) == hash_of(&second),
+        "freezing {value:?} twice gave frozen values which hash differently",
+    );
+}
+
+/// Assert that the frozen form of the unordered collection `C` does not
+/// depend on the order its elements are inserted in.
+///
+/// `C` is built from `elements` in their given order, in reverse, and in a
+/// few orders shuffled by `rng`, and each is frozen and compared.
+///
+/// Map entries should have distinct keys: when two entries share a key, which
+/// one is kept rightly depends on the order they are inserted in.
+///
+/// # Panics
+///
+/// Panics if any two orders give frozen forms which differ or hash
+/// differently.
+#[cfg(feature = "alloc")]
+pub fn assert_order_independent<C, E>(elements: &[E], rng: &mut Rng)
+where
+    C: Freezable + FromIterator<E>,
+    E: Clone + Debug,
+    Frozen<C>: Hash + Eq + Debug,
+{
+    let expected = elements.iter().cloned().collect::<C>().freeze();
+    let expected_hash = hash_of(&expected);
+    let mut order: Vec<E> = elements.iter().rev().cloned().collect();
+    for _ in 0..4 {
+        let frozen = order.iter().cloned().collect::<C>().freeze();
+        assert!(
+            frozen == expected,
+            "inserting {elements:?} in a different order ({order:?}) gave {frozen:?} instead \
+             of {expected:?}",
+        );
+        assert!(
+            hash_of(&frozen) == expected_hash,
+            "inserting {elements:?} in a different order ({order:?}) changed the hash",
+        );
+        rng.shuffle(&mut order);
+    }
+}
@@ -0,0 +1,278 @@
+//! Fingerprints which catch frozen values being mutated.
+//!
+//! With the `verify` feature, [`Frozen::new_verified`] records a hash of the
+//! value it wraps, which [`Frozen::verify`], [`Frozen::thaw`] and
+//! [`Frozen::to_thawed`] check again. The hash is kept in a global table
+//! rather than in the [`Frozen`] itself, so that enabling the feature does not
+//! change the layout of `Frozen` or stop it from being [`Copy`]. The table is
+//! keyed by the type of the frozen value and the [`StableAddress`] of its
+//! contents, which stays the same however the value is moved.
+//!
+//! Values created by [`Frozen::new`] have no fingerprint, and neither do
+//! values whose contents are not on the heap, such as small frozen sets.
+//! Checking them does nothing.
+//!
+//! A fingerprint is removed when its value is thawed. One left behind by a
+//! value dropped while frozen stays in the table until another value of the
+//! same type is frozen at that address, so the table only grows with the
+//! number of distinct addresses used.
+//!
+//! [`Frozen`]: crate::Frozen
+//! [`Frozen::new`]: crate::Frozen::new
+//! [`Frozen::new_verified`]: crate::Frozen::new_verified
+//! [`Frozen::verify`]: crate::Frozen::verify
+//! [`Frozen::thaw`]: crate::Frozen::thaw
+//! [`Frozen::to_thawed`]: crate::Frozen::to_thawed
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "verify")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "verify")]
+use core::any::TypeId;
+#[cfg(feature = "verify")]
+use core::cell::UnsafeCell;
+use core::hash::Hash;
+#[cfg(feature = "verify")]
+use core::hash::Hasher;
+#[cfg(feature = "verify")]
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use core::mem;
+use core::ptr::NonNull;
+#[cfg(feature = "verify")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "verify")]
+use core::{hint, ptr};
+
+#[cfg(feature = "verify")]
+use crate::fnv::Fnv;
+
+/// A frozen form which keeps its contents on the heap, so that their address
+/// identifies the value however it is moved.
+///
+/// [`Frozen::new_verified`](crate::Frozen::new_verified) uses this to find
+/// the fingerprint recorded for a value.
+pub trait StableAddress {
+    /// The address of the contents, or `None` if they are not on the heap.
+    ///
+    /// This must not change while the value is frozen, and no two live values
+    /// of the same type may have the same address.
+    fn stable_address(&self) -> Option<NonNull<()>>;
+}
+/// Empty vectors and vectors of zero-sized types have no address.
+#[cfg(feature = "alloc")]
+impl<T> StableAddress for Vec<T> {
+    fn stable_address(&self) -> Option<NonNull<()>> {
+        if self.is_empty() || mem::size_of::<T>() == 0 {
+            return None;
+        }
+        NonNull::new(self.as_ptr().cast_mut().cast())
+    }
+}
+/// Boxes of zero-sized values have no address.
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> StableAddress for Box<T> {
+    fn stable_address(&self) -> Option<NonNull<()>> {
+        if mem::size_of_val(&**self) == 0 {
+            return None;
+        }
+        Some(NonNull::from(&**self).cast())
+    }
+}
+/// Empty strings have no address.
+#[cfg(feature = "alloc")]
+impl StableAddress for String {
+    fn stable_address(&self) -> Option<NonNull<()>> {
+        if self.is_empty() {
+            return None;
+        }
+        NonNull::new(self.as_ptr().cast_mut().cast())
+    }
+}
+
+/// Record the fingerprint of `value`, replacing any left at its address.
+#[cfg(feature = "verify")]
+pub(crate) fn record<F: Hash + StableAddress>(value: &F) {
+    let Some(address) = value.stable_address() else {
+        return;
+    };
+    let hash = hash(value);
+    let id = type_id::<F>();
+    let form = Form {
+        address: erased_address::<F>,
+        hash: erased_hash::<F>,
+    };
+    TABLE.with(|table| {
+        table.forms.insert(id, form);
+        table
+            .fingerprints
+            .insert((id, address.as_ptr() as usize), hash);
+    });
+    RECORDED.store(true, Ordering::Release);
+}
+#[cfg(not(feature = "verify"))]
+pub(crate) fn record<F: Hash + StableAddress>(_value: &F) {
+}
+
+/// Forget the fingerprint at the address of `value`, which was left by a value
+/// which has since been dropped, or will be by `value` once it is thawed.
+#[cfg(feature = "verify")]
+pub(crate) fn forget<F>(value: &F) {
+    if let Some((id, address, _)) = find(value) {
+        TABLE.with(|table| table.fingerprints.remove(&(id, address)));
+    }
+}
+#[cfg(not(feature = "verify"))]
+pub(crate) fn forget<F>(_value: &F) {
+}
+
+/// Panic if `value` no longer matches the fingerprint recorded for it.
+#[cfg(feature = "verify")]
+pub(crate) fn check<F>(value: &F, type_name: &str) {
+    let Some((id, address, form)) = find(value) else {
+        return;
+    };
+    let Some(expected) =
+        TABLE.with(|table| table.fingerprints.get(&(id, address)).copied())
+    else {
+        return;
+    };
+    // SAFETY: `form` was recorded for values of type `F`.
+    let actual = unsafe { (form.hash)(ptr::from_ref(value).cast()) };
+    assert!(
+        expected == actual,
+        "frozen {type_name} was mutated after freezing (fingerprint {expected:#018x} \
+         became {actual:#018x}); its frozen form must not have interior mutability",
+    );
+}
+#[cfg(not(feature = "verify"))]
+pub(crate) fn check<F>(_value: &F, _type_name: &str) {
+}
+
+/// A function taking a pointer to a value of the type it was recorded for.
+#[cfg(feature = "verify")]
+type Erased<R> = unsafe fn(*const ()) -> R;
+
+/// How to find and hash the values of a type which has been fingerprinted.
+#[cfg(feature = "verify")]
+#[derive(Clone, Copy)]
+struct Form {
+    address: Erased<Option<NonNull<()>>>,
+    hash: Erased<u64>,
+}
+
+#[cfg(feature = "verify")]
+struct Table {
+    forms: BTreeMap<TypeId, Form>,
+    /// Fingerprints, by type and address.
+    fingerprints: BTreeMap<(TypeId, usize), u64>,
+}
+
+/// A spin lock around the table. The functions run under it never call back
+/// into user code, and so can neither panic nor take the lock again.
+#[cfg(feature = "verify")]
+struct Lock {
+    locked: AtomicBool,
+    table: UnsafeCell<Table>,
+}
+// SAFETY: the table is only accessed while holding the lock.
+#[cfg(feature = "verify")]
+unsafe impl Sync for Lock {
+}
+#[cfg(feature = "verify")]
+impl Lock {
+    fn with<R>(&self, f: impl FnOnce(&mut Table) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+        // SAFETY: the lock is held.
+        let result = f(unsafe { &mut *self.table.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+#[cfg(feature = "verify")]
+static TABLE: Lock = Lock {
+    locked: AtomicBool::new(false),
+    table: UnsafeCell::new(Table {
+        forms: BTreeMap::new(),
+        fingerprints: BTreeMap::new(),
+    }),
+};
+/// Whether any fingerprint has been recorded, so that the table need not be
+/// locked until then.
+#[cfg(feature = "verify")]
+static RECORDED: AtomicBool = AtomicBool::new(false);
+
+/// The type, address and form of `value`, if values of its type have been
+/// fingerprinted.
+#[cfg(feature = "verify")]
+fn find<F>(value: &F) -> Option<(TypeId, usize, Form)> {
+    if !RECORDED.load(Ordering::Acquire) {
+        return None;
+    }
+    let id = type_id::<F>();
+    let form = TABLE.with(|table| table.forms.get(&id).copied())?;
+    // SAFETY: `form` was recorded for values of type `F`.
+    let address = unsafe { (form.address)(ptr::from_ref(value).cast()) }?;
+    Some((id, address.as_ptr() as usize, form))
+}
+
+/// # Safety
+///
+/// `value` must point to a live `F`.
+#[cfg(feature = "verify")]
+unsafe fn erased_address<F: StableAddress>(value: *const ()) -> Option<NonNull<()>> {
+    (*value.cast::<F>()).stable_address()
+}
+
+/// # Safety
+///
+/// `value` must point to a live `F`.
+#[cfg(feature = "verify")]
+unsafe fn erased_hash<F: Hash>(value: *const ()) -> u64 {
+    hash(&*value.cast::<F>())
+}
+
+#[cfg(feature = "verify")]
+fn hash<F: Hash + ?Sized>(value: &F) -> u64 {
+    let mut hasher = Fnv::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The [`TypeId`] of `T`, which need not be `'static`. Types differing only
+/// in their lifetimes have the same id, which is fine here as they also have
+/// the same layout and impls.
+#[cfg(feature = "verify")]
+fn type_id<T: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<T>()
+        }
+    }
+    let marker = PhantomData::<T>;
+    // SAFETY: the lifetime is only used to satisfy `TypeId::of`, which does
+    // not depend on lifetimes.
+    let marker = unsafe {
+        mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&marker)
+    };
+    marker.type_id()
+}